@@ -0,0 +1,71 @@
+use ewu_ics_cal::{error::ApiError, exam, options::IcsOptions, utils};
+use reqwest::header::CONTENT_TYPE;
+use serde_json::json;
+use vercel_runtime::{run, Body, Error as VercelError, Request, Response, StatusCode};
+
+#[tokio::main]
+async fn main() -> Result<(), VercelError> {
+    run(exams).await
+}
+
+/// Exams of the comma separated `courses` on the exam schedule at `calendar_path`,
+/// as a calendar, or with `format=json`, the schedule's slots
+pub async fn exams(req: Request) -> Result<Response<Body>, VercelError> {
+    let courses = match utils::get_query_param(&req, "courses") {
+        Ok(courses) => courses
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|x| !x.is_empty())
+            .map(str::to_owned)
+            .collect::<Vec<_>>(),
+        Err(error) => return error.to_response(),
+    };
+    let json = match utils::get_query_param(&req, "format") {
+        Ok(format) => format.is_some_and(|x| x.eq_ignore_ascii_case("json")),
+        Err(error) => return error.to_response(),
+    };
+    let mut options = match IcsOptions::from_request(&req) {
+        Ok(options) => options,
+        Err(error) => return error.to_response(),
+    };
+    // exam schedules have no /cal/<slug>.ics feed
+    options.feed_url = None;
+
+    let schedule = match utils::exam_schedule_from_request(&req).await {
+        Ok(schedule) => schedule,
+        Err(error) => return error.to_response(),
+    };
+
+    utils::calendar_response(&req, &schedule, || {
+        if json {
+            let mut schedule = schedule.clone();
+            if !courses.is_empty() {
+                let courses = courses
+                    .iter()
+                    .map(|x| exam::normalize_course(x))
+                    .collect::<Vec<_>>();
+                schedule
+                    .slots
+                    .retain(|slot| slot.courses.iter().any(|x| courses.contains(x)));
+            }
+            Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header(CONTENT_TYPE, "application/json")
+                .body(json!(schedule).to_string().into())?)
+        } else {
+            if courses.is_empty() {
+                return ApiError::BadRequest("courses parameter is missing".to_string())
+                    .to_response();
+            }
+            let ics = match exam::generate_exam_ics(&schedule, &courses, &options) {
+                Ok(ics) => ics,
+                Err(error) => return error.to_response(),
+            };
+            Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header(CONTENT_TYPE, "text/calendar; charset=utf-8")
+                .body(ics.into())?)
+        }
+    })
+}
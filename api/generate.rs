@@ -1,4 +1,4 @@
-use ewu_ics_cal::{parser, utils::{self, cache_headers}};
+use ewu_ics_cal::{format::OutputFormat, options::IcsOptions, utils};
 use vercel_runtime::{run, Body, Error as VercelError, Request, Response, StatusCode};
 
 #[tokio::main]
@@ -7,26 +7,40 @@ async fn main() -> Result<(), VercelError> {
 }
 
 pub async fn generate(req: Request) -> Result<Response<Body>, VercelError> {
-    let calendar_remote_path = utils::get_calendar_path(&req)?;
-    let calendar = utils::fetch_calendar_details(&calendar_remote_path).await?;
+    let format = match utils::parse_query_param::<OutputFormat>(&req, "format") {
+        Ok(format) => format.unwrap_or_default(),
+        Err(error) => return error.to_response(),
+    };
+    let options = match IcsOptions::from_request(&req) {
+        Ok(options) => options,
+        Err(error) => return error.to_response(),
+    };
 
-    let semester = calendar.semester.clone();
-    let year = calendar.year;
-    let revised_date = calendar.revised_date;
+    let calendar = match utils::calendar_from_request(&req).await {
+        Ok(calendar) => calendar,
+        Err(error) => return error.to_response(),
+    };
 
-    let ics = parser::generate_ics(calendar);
-    let filename = format!("{semester} {year} - {revised_date}.ics");
+    utils::calendar_response(&req, &calendar, || {
+        let filename = format!(
+            "{} {} - {}.{}",
+            calendar.semester,
+            calendar.year,
+            calendar.revised_date,
+            format.extension()
+        );
+        let body = match format.render(calendar.clone(), &options) {
+            Ok(body) => body,
+            Err(error) => return error.to_response(),
+        };
 
-    let mut resp = Response::builder()
-        .status(StatusCode::OK)
-        .header("Content-Type", "text/calendar")
-        .header(
-            "Content-Disposition",
-            format!("attachment; filename=\"{}\"", filename),
-        )
-        .body(ics.into())?;
-
-    cache_headers(&mut resp);
-
-    Ok(resp)
+        Ok(Response::builder()
+            .status(StatusCode::OK)
+            .header("Content-Type", format.content_type())
+            .header(
+                "Content-Disposition",
+                format!("{}; filename=\"{}\"", format.disposition(), filename),
+            )
+            .body(body.into())?)
+    })
 }
@@ -0,0 +1,24 @@
+use ewu_ics_cal::{holidays, utils};
+use reqwest::header::CONTENT_TYPE;
+use serde_json::json;
+use vercel_runtime::{run, Body, Error as VercelError, Request, Response, StatusCode};
+
+#[tokio::main]
+async fn main() -> Result<(), VercelError> {
+    run(check_holidays).await
+}
+
+/// Cross-checks the holidays of a calendar with Bangladesh public holidays
+pub async fn check_holidays(req: Request) -> Result<Response<Body>, VercelError> {
+    let calendar = match utils::calendar_from_request(&req).await {
+        Ok(calendar) => calendar,
+        Err(error) => return error.to_response(),
+    };
+
+    utils::calendar_response(&req, &calendar, || {
+        Ok(Response::builder()
+            .status(StatusCode::OK)
+            .header(CONTENT_TYPE, "application/json")
+            .body(json!(holidays::check(&calendar)).to_string().into())?)
+    })
+}
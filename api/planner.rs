@@ -0,0 +1,30 @@
+use ewu_ics_cal::{pdf, utils};
+use vercel_runtime::{run, Body, Error as VercelError, Request, Response, StatusCode};
+
+#[tokio::main]
+async fn main() -> Result<(), VercelError> {
+    run(planner).await
+}
+
+pub async fn planner(req: Request) -> Result<Response<Body>, VercelError> {
+    let calendar = match utils::calendar_from_request(&req).await {
+        Ok(calendar) => calendar,
+        Err(error) => return error.to_response(),
+    };
+
+    utils::calendar_response(&req, &calendar, || {
+        let filename = format!(
+            "{} {} planner - {}.pdf",
+            calendar.semester, calendar.year, calendar.revised_date
+        );
+
+        Ok(Response::builder()
+            .status(StatusCode::OK)
+            .header("Content-Type", "application/pdf")
+            .header(
+                "Content-Disposition",
+                format!("attachment; filename=\"{}\"", filename),
+            )
+            .body(Body::Binary(pdf::render(&calendar)))?)
+    })
+}
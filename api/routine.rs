@@ -0,0 +1,50 @@
+use ewu_ics_cal::{error::ApiError, options::IcsOptions, routine, utils};
+use reqwest::header::CONTENT_TYPE;
+use vercel_runtime::{run, Body, Error as VercelError, Request, Response, StatusCode};
+
+#[tokio::main]
+async fn main() -> Result<(), VercelError> {
+    run(class_routine).await
+}
+
+/// Weekly class sessions of the course sections POSTed as JSON,
+/// within the semester of the requested calendar
+pub async fn class_routine(req: Request) -> Result<Response<Body>, VercelError> {
+    if req.method() != "POST" {
+        return ApiError::MethodNotAllowed {
+            allow: "POST",
+            detail: "Course sections must be POSTed as JSON".to_string(),
+        }
+        .to_response();
+    }
+
+    let sections = match routine::parse_sections(req.body()) {
+        Ok(sections) => sections,
+        Err(error) => return error.to_response(),
+    };
+    let mut options = match IcsOptions::from_request(&req) {
+        Ok(options) => options,
+        Err(error) => return error.to_response(),
+    };
+    // a routine can't be refreshed from the academic calendar's feed
+    options.feed_url = None;
+    let calendar = match utils::calendar_from_request(&req).await {
+        Ok(calendar) => calendar,
+        Err(error) => return error.to_response(),
+    };
+
+    let ics = match routine::generate_routine_ics(&calendar, &sections, &options) {
+        Ok(ics) => ics,
+        Err(error) => return error.to_response(),
+    };
+    let filename = format!("{} {} routine.ics", calendar.semester, calendar.year);
+
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header(CONTENT_TYPE, "text/calendar; charset=utf-8")
+        .header(
+            "Content-Disposition",
+            format!("attachment; filename=\"{filename}\""),
+        )
+        .body(ics.into())?)
+}
@@ -0,0 +1,29 @@
+use ewu_ics_cal::{stats, utils};
+use reqwest::header::CONTENT_TYPE;
+use serde_json::json;
+use vercel_runtime::{run, Body, Error as VercelError, Request, Response, StatusCode};
+
+#[tokio::main]
+async fn main() -> Result<(), VercelError> {
+    run(semester_stats).await
+}
+
+/// Teaching days of a semester, in total and per weekday
+pub async fn semester_stats(req: Request) -> Result<Response<Body>, VercelError> {
+    let calendar = match utils::calendar_from_request(&req).await {
+        Ok(calendar) => calendar,
+        Err(error) => return error.to_response(),
+    };
+
+    utils::calendar_response(&req, &calendar, || {
+        let stats = match stats::semester_stats(&calendar) {
+            Ok(stats) => stats,
+            Err(error) => return error.to_response(),
+        };
+
+        Ok(Response::builder()
+            .status(StatusCode::OK)
+            .header(CONTENT_TYPE, "application/json")
+            .body(json!(stats).to_string().into())?)
+    })
+}
@@ -0,0 +1,26 @@
+use ewu_ics_cal::utils::{cache_headers, fetch_calendar_lists};
+use ewu_ics_cal::v1;
+use serde_json::to_string;
+use vercel_runtime::{run, Body, Error, Request, Response, StatusCode};
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    run(calendars).await
+}
+
+pub async fn calendars(_req: Request) -> Result<Response<Body>, Error> {
+    match fetch_calendar_lists().await {
+        Ok(lists) => {
+            let cals = lists.iter().map(v1::CalendarList::from).collect::<Vec<_>>();
+
+            let mut response = Response::builder()
+                .status(StatusCode::OK)
+                .header("Content-Type", "application/json")
+                .body(to_string(&cals)?.into())?;
+
+            cache_headers(&mut response);
+            Ok(response)
+        }
+        Err(error) => error.to_response(),
+    }
+}
@@ -0,0 +1,34 @@
+use ewu_ics_cal::locale::Lang;
+use ewu_ics_cal::utils;
+use ewu_ics_cal::v1;
+use reqwest::header::CONTENT_TYPE;
+use serde_json::json;
+use vercel_runtime::{run, Body, Error as VercelError, Request, Response, StatusCode};
+
+#[tokio::main]
+async fn main() -> Result<(), VercelError> {
+    run(entries).await
+}
+
+pub async fn entries(req: Request) -> Result<Response<Body>, VercelError> {
+    let lang = match utils::parse_query_param::<Lang>(&req, "lang") {
+        Ok(lang) => lang.unwrap_or_default(),
+        Err(error) => return error.to_response(),
+    };
+
+    let cal = match utils::calendar_from_request(&req).await {
+        Ok(cal) => cal,
+        Err(error) => return error.to_response(),
+    };
+
+    utils::calendar_response(&req, &cal, || {
+        Ok(Response::builder()
+            .status(StatusCode::OK)
+            .header(CONTENT_TYPE, "application/json")
+            .body(
+                json!(v1::CalendarDetails::new(&cal, lang))
+                    .to_string()
+                    .into(),
+            )?)
+    })
+}
@@ -0,0 +1,18 @@
+use ewu_ics_cal::utils::cache_headers;
+use ewu_ics_cal::v1;
+use vercel_runtime::{run, Body, Error, Request, Response, StatusCode};
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    run(openapi).await
+}
+
+pub async fn openapi(_req: Request) -> Result<Response<Body>, Error> {
+    let mut response = Response::builder()
+        .status(StatusCode::OK)
+        .header("Content-Type", "application/json")
+        .body(v1::openapi().to_string().into())?;
+
+    cache_headers(&mut response);
+    Ok(response)
+}
@@ -10,14 +10,48 @@ const fetchCalendar = () =>
     mode: "no-cors",
   });
 
+// messages may repeat parts of the request, so they are only ever set as text
+const showMessage = (title, ...hint) => {
+  const message = document.createElement("p");
+  message.append(`${title} :-( `, document.createElement("br"), ...hint);
+  root.replaceChildren(message);
+};
+
+const ewubdHint = () => {
+  const link = document.createElement("a");
+  link.href = "//ewubd.edu";
+  link.textContent = "ewubd.edu";
+  return ["Check if ", link, " loads correctly"];
+};
+
+// errors from the API are RFC 7807 problem details
+const showProblem = async (res) => {
+  const problem = res.headers
+    .get("Content-Type")
+    ?.startsWith("application/problem+json")
+    ? await res.json().catch(() => null)
+    : null;
+
+  if (problem == null) {
+    showMessage("Couldn't generate calendar", ...ewubdHint());
+    return;
+  }
+
+  if (problem.status === 502) {
+    showMessage(problem.title, ...ewubdHint());
+  } else {
+    showMessage(problem.title, problem.detail ?? "");
+  }
+};
+
 const calendar = await fetchCalendar()
   .then((res) => {
     if (!res.ok) {
-      root.innerHTML = `<p>Couldn't generate calendar :-( <br />Check if <a href="//ewubd.edu">ewubd.edu</a> loads correctly</p>`;
+      return showProblem(res);
     } else return res.json();
   })
   .catch((e) => {
-    root.innerHTML = `<p>Couldn't generate calendar :-( <br />(${e.message})</p>`;
+    showMessage("Couldn't generate calendar", `(${e.message})`);
   });
 
 if (calendar != null) {
@@ -25,15 +59,16 @@ if (calendar != null) {
   const revisedDate = new Date(calendar["revised_date"]);
   const semester = calendar["semester"];
   const year = calendar["year"];
+  // pretty urls avoid encoded query strings, which some calendar clients mangle
+  const slug = encodeURIComponent(
+    calendarRemotePath.split("/").filter(Boolean).pop()
+  );
   const fileUri = (protocol) =>
-    protocol +
-    window.location.host +
-    "/api/generate?calendar_path=" +
-    calendarRemotePath;
+    protocol + window.location.host + "/cal/" + slug + ".ics";
 
   document.title = `${semester} ${year} calendar - EWU Calendar`;
-  const doc = `<h2>${semester} ${year}</h2>
-        <h3>${calendarName}</h3>
+  const doc = `<h2 id="semester"></h2>
+        <h3 id="calendar-name"></h3>
         <p>Last updated: ${revisedDate.toDateString()}</p>
         <p>
           <ul>
@@ -46,13 +81,14 @@ if (calendar != null) {
             <li><a class="button" target="_blank" href="https://outlook.live.com/calendar/0/addfromweb/?url=${fileUri(
               "https://"
             )}">Add to Outlook.com</a></li>
-            <li>or <a href="/api/generate?calendar_path=${encodeURIComponent(
-              calendarRemotePath
-            )}">download .ics file</a></li>
+            <li>or <a href="/cal/${slug}.ics">download .ics file</a></li>
+            <li>or <a href="/api/planner?slug=${slug}">print a semester planner (PDF)</a></li>
           </ul>
         </p>`;
 
   root.innerHTML = doc;
+  document.getElementById("semester").textContent = `${semester} ${year}`;
+  document.getElementById("calendar-name").textContent = calendarName;
 
   const calendarEl = document.getElementById("calendar");
 
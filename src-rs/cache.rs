@@ -0,0 +1,340 @@
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Most responses kept in memory, past which the least recently fetched one is dropped
+const MAX_ENTRIES: usize = 256;
+
+#[derive(Debug, Clone, Copy)]
+pub struct CachePolicy {
+    /// How long a response is served without contacting upstream
+    pub ttl: Duration,
+    /// How long past `ttl` a response is served while it is refreshed in the background
+    pub stale_while_revalidate: Duration,
+    /// How long past `ttl` a response is served when upstream can't be reached
+    pub stale_if_error: Duration,
+}
+
+impl Default for CachePolicy {
+    fn default() -> Self {
+        CachePolicy {
+            ttl: Duration::from_secs(60 * 60),
+            stale_while_revalidate: Duration::from_secs(24 * 60 * 60),
+            stale_if_error: Duration::from_secs(7 * 24 * 60 * 60),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Freshness {
+    Fresh,
+    Stale,
+    Expired,
+}
+
+impl CachePolicy {
+    pub fn freshness(&self, age: Duration) -> Freshness {
+        if age < self.ttl {
+            Freshness::Fresh
+        } else if age < self.ttl + self.stale_while_revalidate {
+            Freshness::Stale
+        } else {
+            Freshness::Expired
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedResponse {
+    body: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    /// seconds since unix epoch
+    fetched_at: u64,
+}
+
+impl CachedResponse {
+    fn age(&self) -> Duration {
+        Duration::from_secs(now().saturating_sub(self.fetched_at))
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Caches upstream pages in memory, and on disk if a directory is given,
+/// revalidating them with `ETag` and `Last-Modified` once they go stale
+#[derive(Debug)]
+pub struct ResponseCache {
+    policy: CachePolicy,
+    dir: Option<PathBuf>,
+    entries: Mutex<HashMap<String, CachedResponse>>,
+    /// Urls being revalidated in the background
+    revalidating: Mutex<HashSet<String>>,
+    client: reqwest::Client,
+}
+
+impl ResponseCache {
+    pub fn new(policy: CachePolicy, dir: Option<PathBuf>) -> Self {
+        ResponseCache {
+            policy,
+            dir,
+            entries: Mutex::new(HashMap::new()),
+            revalidating: Mutex::new(HashSet::new()),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Shared cache, persisted to `EWU_CACHE_DIR` if it is set
+    pub fn global() -> &'static Arc<ResponseCache> {
+        static CACHE: OnceLock<Arc<ResponseCache>> = OnceLock::new();
+        CACHE.get_or_init(|| {
+            Arc::new(ResponseCache::new(
+                CachePolicy::default(),
+                env::var_os("EWU_CACHE_DIR").map(PathBuf::from),
+            ))
+        })
+    }
+
+    pub async fn fetch(
+        self: &Arc<Self>,
+        url: &str,
+    ) -> Result<String, Box<dyn Error + Send + Sync>> {
+        let cached = self.lookup(url);
+
+        match cached {
+            Some(cached) => match self.policy.freshness(cached.age()) {
+                Freshness::Fresh => Ok(cached.body),
+                Freshness::Stale => {
+                    let body = cached.body.clone();
+                    // a url already being revalidated is left to that revalidation
+                    let first = match self.revalidating.lock() {
+                        Ok(mut revalidating) => revalidating.insert(url.to_owned()),
+                        Err(_) => false,
+                    };
+                    if first {
+                        let cache = Arc::clone(self);
+                        let url = url.to_owned();
+                        tokio::spawn(async move {
+                            let _ = cache.revalidate(&url, Some(cached)).await;
+                            if let Ok(mut revalidating) = cache.revalidating.lock() {
+                                revalidating.remove(&url);
+                            }
+                        });
+                    }
+                    Ok(body)
+                }
+                Freshness::Expired => {
+                    let max_age = self.policy.ttl + self.policy.stale_if_error;
+                    match self.revalidate(url, Some(cached.clone())).await {
+                        Ok(body) => Ok(body),
+                        Err(_) if cached.age() < max_age => Ok(cached.body),
+                        Err(e) => Err(e),
+                    }
+                }
+            },
+            None => self.revalidate(url, None).await,
+        }
+    }
+
+    async fn revalidate(
+        &self,
+        url: &str,
+        cached: Option<CachedResponse>,
+    ) -> Result<String, Box<dyn Error + Send + Sync>> {
+        let mut req = self.client.get(url);
+        if let Some(cached) = &cached {
+            if let Some(etag) = &cached.etag {
+                req = req.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &cached.last_modified {
+                req = req.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+
+        let resp = req.send().await?;
+
+        let fresh = match cached {
+            Some(mut cached) if resp.status() == StatusCode::NOT_MODIFIED => {
+                cached.fetched_at = now();
+                cached
+            }
+            _ => {
+                let resp = resp.error_for_status()?;
+                let header = |name| {
+                    resp.headers()
+                        .get(name)
+                        .and_then(|x| x.to_str().ok())
+                        .map(|x| x.to_owned())
+                };
+                let etag = header(ETAG);
+                let last_modified = header(LAST_MODIFIED);
+
+                CachedResponse {
+                    body: resp.text().await?,
+                    etag,
+                    last_modified,
+                    fetched_at: now(),
+                }
+            }
+        };
+
+        let body = fresh.body.clone();
+        self.store(url, fresh);
+        Ok(body)
+    }
+
+    fn lookup(&self, url: &str) -> Option<CachedResponse> {
+        let in_memory = self.entries.lock().ok()?.get(url).cloned();
+
+        in_memory.or_else(|| {
+            let raw = fs::read_to_string(self.disk_path(url)?).ok()?;
+            let cached: CachedResponse = serde_json::from_str(&raw).ok()?;
+            self.entries
+                .lock()
+                .ok()?
+                .insert(url.to_owned(), cached.clone());
+            Some(cached)
+        })
+    }
+
+    fn store(&self, url: &str, cached: CachedResponse) {
+        if let Some(path) = self.disk_path(url) {
+            // disk cache is best effort; memory cache still works without it
+            let _ = path
+                .parent()
+                .map(fs::create_dir_all)
+                .unwrap_or(Ok(()))
+                .and_then(|_| fs::write(&path, serde_json::to_string(&cached)?));
+        }
+        if let Ok(mut entries) = self.entries.lock() {
+            if entries.len() >= MAX_ENTRIES && !entries.contains_key(url) {
+                let oldest = entries
+                    .iter()
+                    .min_by_key(|(_, cached)| cached.fetched_at)
+                    .map(|(url, _)| url.clone());
+                if let Some(oldest) = oldest {
+                    entries.remove(&oldest);
+                }
+            }
+            entries.insert(url.to_owned(), cached);
+        }
+    }
+
+    fn disk_path(&self, url: &str) -> Option<PathBuf> {
+        let hash = xxhash_rust::xxh3::xxh3_64(url.as_bytes());
+        self.dir
+            .as_ref()
+            .map(|dir| dir.join(format!("{:x}.json", hash)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{stand_in_server, Received};
+
+    /// Serves `etag` once, then answers revalidations with 304
+    fn respond(request: &Received) -> &'static [u8] {
+        match request.header("if-none-match") {
+            Some(_) => b"HTTP/1.1 304 Not Modified\r\nConnection: close\r\n\r\n",
+            None => b"HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Length: 5\r\nConnection: close\r\n\r\nhello",
+        }
+    }
+
+    #[test]
+    fn test_freshness() {
+        let policy = CachePolicy {
+            ttl: Duration::from_secs(10),
+            stale_while_revalidate: Duration::from_secs(10),
+            stale_if_error: Duration::from_secs(100),
+        };
+
+        assert_eq!(policy.freshness(Duration::from_secs(5)), Freshness::Fresh);
+        assert_eq!(policy.freshness(Duration::from_secs(15)), Freshness::Stale);
+        assert_eq!(
+            policy.freshness(Duration::from_secs(25)),
+            Freshness::Expired
+        );
+    }
+
+    #[tokio::test]
+    async fn test_revalidates_with_etag() {
+        let (url, handle) = stand_in_server(2, respond);
+        let url = format!("{url}/academic-calendar");
+        let policy = CachePolicy {
+            ttl: Duration::ZERO,
+            stale_while_revalidate: Duration::ZERO,
+            stale_if_error: Duration::from_secs(60),
+        };
+        let cache = Arc::new(ResponseCache::new(policy, None));
+
+        assert_eq!(cache.fetch(&url).await.unwrap(), "hello");
+        assert_eq!(cache.fetch(&url).await.unwrap(), "hello");
+        let if_none_match = handle
+            .join()
+            .unwrap()
+            .iter()
+            .map(|request| request.header("if-none-match").map(str::to_owned))
+            .collect::<Vec<_>>();
+        assert_eq!(if_none_match, vec![None, Some("\"v1\"".to_owned())]);
+
+        // upstream is gone now, so the stale copy is served
+        assert_eq!(cache.fetch(&url).await.unwrap(), "hello");
+    }
+
+    #[tokio::test]
+    async fn test_revalidates_stale_url_once() {
+        let (url, handle) = stand_in_server(2, respond);
+        let policy = CachePolicy {
+            ttl: Duration::ZERO,
+            stale_while_revalidate: Duration::from_secs(60),
+            stale_if_error: Duration::ZERO,
+        };
+        let cache = Arc::new(ResponseCache::new(policy, None));
+
+        assert_eq!(cache.fetch(&url).await.unwrap(), "hello");
+        assert_eq!(cache.fetch(&url).await.unwrap(), "hello");
+        assert_eq!(cache.fetch(&url).await.unwrap(), "hello");
+        assert_eq!(cache.revalidating.lock().unwrap().len(), 1);
+
+        // both stale hits share the one revalidation, which is let through to the stand-in
+        tokio::task::spawn_blocking(|| handle.join().unwrap())
+            .await
+            .unwrap();
+        while !cache.revalidating.lock().unwrap().is_empty() {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    #[test]
+    fn test_entries_are_capped() {
+        let cache = ResponseCache::new(CachePolicy::default(), None);
+        let cached = |fetched_at| CachedResponse {
+            body: String::new(),
+            etag: None,
+            last_modified: None,
+            fetched_at,
+        };
+
+        for i in 0..MAX_ENTRIES as u64 + 1 {
+            cache.store(&format!("http://x/{i}"), cached(100 + i));
+        }
+
+        let entries = cache.entries.lock().unwrap();
+        assert_eq!(entries.len(), MAX_ENTRIES);
+        assert!(!entries.contains_key("http://x/0"));
+        assert!(entries.contains_key(&format!("http://x/{MAX_ENTRIES}")));
+    }
+}
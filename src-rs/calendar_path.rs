@@ -0,0 +1,144 @@
+use regex::Regex;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use crate::error::ApiError;
+use crate::parser::CalendarList;
+
+pub const ORIGIN: &str = "https://www.ewubd.edu";
+
+/// A path to a calendar page on ewubd.edu, eg: /academic-calendar-details/spring-2024-graduate.
+/// Only paths made of a known prefix and a plain slug are accepted,
+/// so user input can't point the fetcher at another host or page
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CalendarPath(String);
+
+impl CalendarPath {
+    pub const PREFIX: &'static str = "/academic-calendar-details/";
+
+    pub fn parse(path: &str) -> Result<Self, ApiError> {
+        let path = path.trim();
+        let path = path.strip_prefix(ORIGIN).unwrap_or(path);
+
+        path.strip_prefix(Self::PREFIX)
+            .ok_or_else(|| {
+                ApiError::BadRequest(format!("calendar_path must start with {}", Self::PREFIX))
+            })
+            .and_then(Self::from_slug)
+    }
+
+    pub fn from_slug(slug: &str) -> Result<Self, ApiError> {
+        static SLUG: OnceLock<Regex> = OnceLock::new();
+        let slug_regex = SLUG.get_or_init(|| Regex::new(r"^[a-z0-9]+(-[a-z0-9]+)*$").unwrap());
+
+        if slug.len() > 100 || !slug_regex.is_match(slug) {
+            return Err(ApiError::BadRequest(
+                "Not a valid calendar, its name may only have lowercase letters, digits and dashes"
+                    .to_string(),
+            ));
+        }
+
+        Ok(CalendarPath(format!("{}{}", Self::PREFIX, slug)))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn slug(&self) -> &str {
+        &self.0[Self::PREFIX.len()..]
+    }
+
+    pub fn url(&self) -> String {
+        format!("{}{}", ORIGIN, self.0)
+    }
+
+    /// Checks whether the calendar or exam schedule appears on the academic calendar listing
+    pub fn is_listed(&self, lists: &[CalendarList]) -> bool {
+        lists
+            .iter()
+            .flat_map(|list| &list.programs)
+            .flat_map(|program| program.calendars.iter().chain(&program.exam_schedules))
+            .any(|cal| CalendarPath::parse(&cal.url).is_ok_and(|path| path == *self))
+    }
+}
+
+impl FromStr for CalendarPath {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        CalendarPath::parse(s)
+    }
+}
+
+impl fmt::Display for CalendarPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{Calendar, Program};
+
+    #[test]
+    fn test_parse_calendar_path() {
+        let path = CalendarPath::parse("/academic-calendar-details/spring-2024-graduate").unwrap();
+        assert_eq!(path.slug(), "spring-2024-graduate");
+        assert_eq!(
+            path.url(),
+            "https://www.ewubd.edu/academic-calendar-details/spring-2024-graduate"
+        );
+        assert_eq!(
+            CalendarPath::parse("https://www.ewubd.edu/academic-calendar-details/fall-2023"),
+            CalendarPath::from_slug("fall-2023")
+        );
+
+        let rejected = [
+            "@evil.com/x",
+            "/academic-calendar",
+            "/academic-calendar-details/",
+            "/academic-calendar-details/../admin",
+            "/academic-calendar-details/fall-2023?x=1",
+            "/academic-calendar-details/fall-2023@evil.com",
+            "//evil.com/academic-calendar-details/fall-2023",
+        ];
+        for input in rejected {
+            assert!(CalendarPath::parse(input).is_err(), "{input} was accepted");
+        }
+
+        // the page shows the detail, so it mustn't repeat the input
+        let error = CalendarPath::from_slug("<img src=x onerror=alert(1)>").unwrap_err();
+        assert!(!error.detail().contains('<'), "{}", error.detail());
+    }
+
+    #[test]
+    fn test_is_listed() {
+        let lists = vec![CalendarList {
+            year: "2024".to_string(),
+            programs: vec![Program {
+                program_type: "Graduate".to_string(),
+                calendars: vec![Calendar {
+                    name: "Spring 2024".to_string(),
+                    url: "/academic-calendar-details/spring-2024-graduate".to_string(),
+                }],
+                exam_schedules: vec![Calendar {
+                    name: "Final Exam Schedule Spring 2024".to_string(),
+                    url: "/academic-calendar-details/final-exam-spring-2024-graduate".to_string(),
+                }],
+            }],
+        }];
+
+        assert!(CalendarPath::from_slug("spring-2024-graduate")
+            .unwrap()
+            .is_listed(&lists));
+        assert!(CalendarPath::from_slug("final-exam-spring-2024-graduate")
+            .unwrap()
+            .is_listed(&lists));
+        assert!(!CalendarPath::from_slug("fall-2024-graduate")
+            .unwrap()
+            .is_listed(&lists));
+    }
+}
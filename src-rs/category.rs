@@ -0,0 +1,163 @@
+use serde::Serialize;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::ApiError;
+
+/// Kind of an academic calendar event, guessed from its text
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Category {
+    Holiday,
+    Exam,
+    Deadline,
+    Registration,
+    Classes,
+    Other,
+}
+
+const HOLIDAY_KEYWORDS: [&str; 17] = [
+    "holiday",
+    "vacation",
+    "break",
+    "recess",
+    "closed",
+    "no class",
+    "eid",
+    "puja",
+    "boishakh",
+    "christmas",
+    "victory day",
+    "independence day",
+    "language martyrs",
+    "ashura",
+    "shab-e",
+    "purnima",
+    "may day",
+];
+const REGISTRATION_KEYWORDS: [&str; 6] = [
+    "registration",
+    "advising",
+    "add/drop",
+    "add and drop",
+    "enrollment",
+    "admission",
+];
+const DEADLINE_KEYWORDS: [&str; 8] = [
+    "last date",
+    "deadline",
+    "payment",
+    "fee",
+    "withdraw",
+    "withdrawal",
+    "submission",
+    "submit",
+];
+
+/// Whether `text` has `word`, or its plural, as a whole word
+fn has_word(text: &str, word: &str) -> bool {
+    let is_word_char = |c: char| c.is_alphanumeric();
+    text.match_indices(word).any(|(i, _)| {
+        let after = &text[i + word.len()..];
+        !text[..i].ends_with(is_word_char)
+            && ["es", "s", ""].iter().any(|suffix| {
+                after
+                    .strip_prefix(suffix)
+                    .is_some_and(|rest| !rest.starts_with(is_word_char))
+            })
+    })
+}
+
+impl Category {
+    pub const ALL: [Category; 6] = [
+        Category::Holiday,
+        Category::Exam,
+        Category::Deadline,
+        Category::Registration,
+        Category::Classes,
+        Category::Other,
+    ];
+
+    /// Classifies an event by keywords, checked from the most to the least specific.
+    /// Keywords match whole words, so "fee" doesn't match "feedback"
+    pub fn classify(event: &str) -> Category {
+        let event = event.to_lowercase();
+        let has_any = |keywords: &[&str]| keywords.iter().any(|x| has_word(&event, x));
+
+        if has_any(&["exam", "examination"]) {
+            Category::Exam
+        } else if has_any(&HOLIDAY_KEYWORDS) {
+            Category::Holiday
+        } else if has_any(&REGISTRATION_KEYWORDS) {
+            Category::Registration
+        } else if has_any(&DEADLINE_KEYWORDS) {
+            Category::Deadline
+        } else if has_any(&["class", "orientation"]) {
+            Category::Classes
+        } else if has_word(&event, "last day") {
+            Category::Deadline
+        } else {
+            Category::Other
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Category::Holiday => "holiday",
+            Category::Exam => "exam",
+            Category::Deadline => "deadline",
+            Category::Registration => "registration",
+            Category::Classes => "classes",
+            Category::Other => "other",
+        }
+    }
+}
+
+impl FromStr for Category {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Category::ALL
+            .into_iter()
+            .find(|category| category.as_str().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| ApiError::BadRequest(format!("Unknown category \"{s}\"")))
+    }
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        let cases = [
+            ("Mid-term Examination", Category::Exam),
+            (
+                "Eid-ul-Fitr (University will remain closed)",
+                Category::Holiday,
+            ),
+            ("Semester Break", Category::Holiday),
+            ("Online Advising and Registration", Category::Registration),
+            ("Last date of 2nd installment payment", Category::Deadline),
+            ("Last day of classes", Category::Classes),
+            ("Classes begin", Category::Classes),
+            ("Submission of grades", Category::Deadline),
+            ("Convocation", Category::Other),
+            ("Final Exams", Category::Exam),
+            ("Last date of course withdrawal", Category::Deadline),
+            ("Course feedback survey", Category::Other),
+            ("Seminar on Leidenfrost effect", Category::Other),
+            ("Shab-e-Barat", Category::Holiday),
+        ];
+
+        for (event, expected) in cases {
+            assert_eq!(Category::classify(event), expected, "{event}");
+        }
+    }
+}
@@ -0,0 +1,315 @@
+//! Checks iCalendar output against the RFC 5545 rules calendar clients trip over:
+//! line endings and folding, text escaping, required properties and unique UIDs
+
+use std::collections::HashSet;
+
+use crate::ical::{self, Component};
+
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Properties whose value type is TEXT and so must be escaped
+const TEXT_PROPERTIES: [&str; 9] = [
+    "SUMMARY",
+    "DESCRIPTION",
+    "LOCATION",
+    "COMMENT",
+    "CATEGORIES",
+    "NAME",
+    "X-WR-CALNAME",
+    "X-WR-CALDESC",
+    "TZID",
+];
+
+/// Properties whose value must be a UTC date-time
+const UTC_PROPERTIES: [&str; 3] = ["DTSTAMP", "LAST-MODIFIED", "CREATED"];
+
+#[derive(Debug, PartialEq)]
+pub struct Violation {
+    /// 1-based physical line number, or 0 if the violation isn't tied to a line
+    pub line: usize,
+    pub message: String,
+}
+
+fn violation(line: usize, message: impl Into<String>) -> Violation {
+    Violation {
+        line,
+        message: message.into(),
+    }
+}
+
+fn check_lines(ics: &[u8], violations: &mut Vec<Violation>) {
+    if !ics.ends_with(b"\r\n") {
+        violations.push(violation(0, "Output doesn't end with CRLF"));
+    }
+
+    let mut start = 0;
+    let mut number = 0;
+    while start < ics.len() {
+        number += 1;
+        let end = ics[start..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .map_or(ics.len(), |i| start + i);
+        let line = &ics[start..end];
+        let line = match line.strip_suffix(b"\r") {
+            Some(line) => line,
+            None => {
+                if end < ics.len() {
+                    violations.push(violation(number, "Line ends with a bare LF"));
+                }
+                line
+            }
+        };
+
+        if line.contains(&b'\r') {
+            violations.push(violation(number, "Line contains a bare CR"));
+        }
+        if line.len() > MAX_LINE_OCTETS {
+            violations.push(violation(
+                number,
+                format!("Line is {} octets long, over {MAX_LINE_OCTETS}", line.len()),
+            ));
+        }
+        // each folded line must be valid on its own, so folding can't split a character
+        if std::str::from_utf8(line).is_err() {
+            violations.push(violation(number, "Line isn't valid UTF-8"));
+        }
+
+        start = end + 1;
+    }
+}
+
+/// A backslash may only escape a backslash, semicolon, comma or newline.
+/// Semicolons and commas must be escaped, except commas separating CATEGORIES
+fn check_text(name: &str, value: &str) -> Option<String> {
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('\\' | ';' | ',' | 'n' | 'N') => {}
+                other => return Some(format!("{name} has an invalid escape \\{other:?}")),
+            },
+            ';' => return Some(format!("{name} has an unescaped ';'")),
+            ',' if name != "CATEGORIES" => return Some(format!("{name} has an unescaped ','")),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn check_component(
+    component: &Component,
+    uids: &mut HashSet<String>,
+    violations: &mut Vec<Violation>,
+) {
+    let context = &component.name;
+    let require = |names: &[&str], violations: &mut Vec<Violation>| {
+        for name in names {
+            if component.property(name).is_none() {
+                violations.push(violation(0, format!("{context} is missing {name}")));
+            }
+        }
+    };
+
+    match component.name.as_str() {
+        "VCALENDAR" => require(&["PRODID", "VERSION"], violations),
+        "VEVENT" => {
+            require(&["UID", "DTSTAMP", "DTSTART"], violations);
+            if let Some(uid) = component.property("UID") {
+                if !uids.insert(uid.value.clone()) {
+                    violations.push(violation(0, format!("Duplicate UID {}", uid.value)));
+                }
+            }
+            if component.property("DTEND").is_some() && component.property("DURATION").is_some() {
+                violations.push(violation(0, "VEVENT has both DTEND and DURATION"));
+            }
+            if let (Some(start), Some(end)) =
+                (component.property("DTSTART"), component.property("DTEND"))
+            {
+                // both are in the same basic format, so they compare lexically
+                if end.value <= start.value {
+                    violations.push(violation(
+                        0,
+                        format!("DTEND {} isn't after DTSTART {}", end.value, start.value),
+                    ));
+                }
+            }
+        }
+        "VTIMEZONE" => require(&["TZID"], violations),
+        "STANDARD" | "DAYLIGHT" => require(&["DTSTART", "TZOFFSETFROM", "TZOFFSETTO"], violations),
+        "VALARM" => require(&["ACTION", "TRIGGER"], violations),
+        _ => {}
+    }
+
+    for property in &component.properties {
+        if TEXT_PROPERTIES.contains(&property.name.as_str()) {
+            if let Some(message) = check_text(&property.name, &property.value) {
+                violations.push(violation(0, format!("{context}: {message}")));
+            }
+        }
+        if UTC_PROPERTIES.contains(&property.name.as_str()) && !property.value.ends_with('Z') {
+            violations.push(violation(
+                0,
+                format!(
+                    "{context}: {} {} isn't in UTC",
+                    property.name, property.value
+                ),
+            ));
+        }
+    }
+
+    for child in &component.components {
+        check_component(child, uids, violations);
+    }
+}
+
+/// Lists every rule the iCalendar output breaks
+pub fn validate(ics: &[u8]) -> Vec<Violation> {
+    let mut violations = vec![];
+    check_lines(ics, &mut violations);
+
+    let text = String::from_utf8_lossy(ics);
+    match ical::parse(&text) {
+        Ok(calendar) if calendar.name == "VCALENDAR" => {
+            check_component(&calendar, &mut HashSet::new(), &mut violations)
+        }
+        Ok(other) => violations.push(violation(
+            0,
+            format!("Top level component is {}", other.name),
+        )),
+        Err(e) => violations.push(violation(0, e.to_string())),
+    }
+
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::locale::Lang;
+    use crate::options::{IcsOptions, Reminder};
+    use crate::parser::{generate_ics, generate_ics_with_options, CalendarDetails, Entry};
+    use crate::test_util::{calendar, date};
+    use chrono::NaiveDate;
+
+    fn tricky_calendar() -> CalendarDetails {
+        let entry = |start: NaiveDate, end, event: &str| Entry {
+            date_text: start.format("%-d %B (%A)").to_string(),
+            ..crate::test_util::entry(start, end, event)
+        };
+
+        CalendarDetails {
+            calendar_name: "Undergraduate; Pharmacy, Law".to_string(),
+            ..calendar(vec![
+                entry(date(1, 7), None, "Classes begin"),
+                entry(date(2, 21), None, "Shaheed Dibosh, International Mother Language Day; University closed"),
+                entry(date(4, 9), Some(date(4, 13)), "ঈদুল ফিতর (Eid-ul-Fitr) ছুটি, বিশ্ববিদ্যালয় বন্ধ থাকবে এবং সকল ক্লাস স্থগিত"),
+                entry(date(4, 14), None, "Pohela Boishakh \\ Bengali New Year\nHoliday"),
+                entry(date(5, 1), None, "Holiday"),
+                entry(date(5, 22), None, "Holiday"),
+                // a row repeated on the page
+                entry(date(5, 22), None, "Holiday"),
+                entry(
+                    date(5, 26),
+                    Some(date(5, 30)),
+                    "Final Examinations of all undergraduate programs, including thesis defense and project presentations",
+                ),
+            ])
+        }
+    }
+
+    fn assert_conforms(ics: &str) {
+        let violations = validate(ics.as_bytes());
+        assert!(violations.is_empty(), "{violations:#?}\n{ics}");
+    }
+
+    #[test]
+    fn test_generated_ics_conforms() {
+        assert_conforms(&generate_ics(tricky_calendar()));
+
+        let options = IcsOptions {
+            location: "Aftabnagar, Dhaka; Main campus".to_string(),
+            source_url: Some(
+                "https://www.ewubd.edu/academic-calendar-details/spring-2024-undergraduate"
+                    .to_string(),
+            ),
+            feed_url: Some(
+                "https://ewubd-ical-generator.vercel.app/cal/spring-2024-undergraduate.ics"
+                    .to_string(),
+            ),
+            geo: Some((23.7686, 90.4255)),
+            organizer: Some("registrar@ewubd.edu".to_string()),
+            alarms: vec![Reminder::days(1), Reminder { minutes: 90 }],
+            lang: Lang::Bn,
+            annotate_dates: true,
+            ..Default::default()
+        };
+        let ics = generate_ics_with_options(tricky_calendar(), &options);
+        assert_conforms(&ics);
+
+        // translated text is tagged with its language, alarms included
+        let lines = ics.lines().collect::<Vec<_>>();
+        assert!(lines.iter().any(|x| x.starts_with("SUMMARY;LANGUAGE=bn:")));
+        assert!(lines
+            .iter()
+            .any(|x| x.starts_with("DESCRIPTION;LANGUAGE=bn:")));
+        assert!(!lines.iter().any(|x| x.starts_with("DESCRIPTION:")));
+    }
+
+    #[test]
+    fn test_long_multibyte_summary_is_folded() {
+        let mut calendar = tricky_calendar();
+        calendar.entries[0].event = "ঈদুল ফিতর ও বাংলা নববর্ষের ছুটি শেষে সকল ক্লাস আবার শুরু".repeat(2);
+
+        let ics = generate_ics(calendar);
+
+        assert_conforms(&ics);
+        let summary = ics
+            .split("\r\n")
+            .skip_while(|x| !x.starts_with("SUMMARY:ঈদুল"))
+            .take_while(|x| x.starts_with("SUMMARY:") || x.starts_with(' '))
+            .count();
+        assert!(summary > 2, "{ics}");
+
+        // the ics crate folds continuation lines to 76 octets, counting the leading space
+        let unfolded = format!(
+            "BEGIN:VCALENDAR\r\nPRODID:x\r\nVERSION:2.0\r\nX-A:{}\r\n {}\r\nEND:VCALENDAR\r\n",
+            "a".repeat(71),
+            "b".repeat(75)
+        );
+        assert_eq!(
+            validate(unfolded.as_bytes()),
+            vec![violation(5, "Line is 76 octets long, over 75")]
+        );
+    }
+
+    #[test]
+    fn test_detects_violations() {
+        let ics = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nBEGIN:VEVENT\r\nUID:1\r\nDTSTAMP:20240101T000000\r\nDTSTART;VALUE=DATE:20240105\r\nDTEND;VALUE=DATE:20240105\r\nSUMMARY:Add, drop\nEND:VEVENT\r\nEND:VCALENDAR";
+
+        let messages = validate(ics.as_bytes())
+            .into_iter()
+            .map(|x| x.message)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            messages,
+            vec![
+                "Output doesn't end with CRLF",
+                "Line ends with a bare LF",
+                "VCALENDAR is missing PRODID",
+                "DTEND 20240105 isn't after DTSTART 20240105",
+                "VEVENT: DTSTAMP 20240101T000000 isn't in UTC",
+                "VEVENT: SUMMARY has an unescaped ','",
+            ]
+        );
+
+        let split_character = "BEGIN:VCALENDAR\r\nSUMMARY:\u{0988}\r\nEND:VCALENDAR\r\n".as_bytes();
+        let mut folded = split_character[..27].to_vec();
+        folded.extend_from_slice(b"\r\n ");
+        folded.extend_from_slice(&split_character[27..]);
+        assert!(validate(&folded)
+            .iter()
+            .any(|x| x.message == "Line isn't valid UTF-8"));
+    }
+}
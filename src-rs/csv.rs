@@ -0,0 +1,94 @@
+//! CSV export of calendar entries, readable by Excel and other spreadsheets
+
+use crate::category::Category;
+use crate::parser::CalendarDetails;
+
+const HEADER: [&str; 6] = [
+    "Start Date",
+    "End Date",
+    "Weekday",
+    "Duration (days)",
+    "Category",
+    "Title",
+];
+
+/// Lets Excel tell the file is UTF-8, which it otherwise reads in the system code page
+const BOM: &str = "\u{feff}";
+
+/// Quotes a field if it contains a delimiter, quote or line break, as in RFC 4180.
+/// A field a spreadsheet would run as a formula is prefixed with `'`, which keeps it text
+fn escape_field(field: &str) -> String {
+    let field = if field.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{field}")
+    } else {
+        field.to_owned()
+    };
+
+    if field.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field
+    }
+}
+
+fn write_row(out: &mut String, fields: &[&str]) {
+    let row = fields
+        .iter()
+        .map(|field| escape_field(field))
+        .collect::<Vec<_>>()
+        .join(",");
+    out.push_str(&row);
+    out.push_str("\r\n");
+}
+
+pub fn render(calendar: &CalendarDetails) -> String {
+    let mut out = BOM.to_owned();
+    write_row(&mut out, &HEADER);
+
+    for entry in &calendar.entries {
+        let start = entry.start();
+        let end = entry.end();
+        let weekday = if start == end {
+            start.format("%A").to_string()
+        } else {
+            format!("{}-{}", start.format("%A"), end.format("%A"))
+        };
+
+        write_row(
+            &mut out,
+            &[
+                &start.to_string(),
+                &end.to_string(),
+                &weekday,
+                &entry.days().to_string(),
+                Category::classify(&entry.event).as_str(),
+                &entry.event,
+            ],
+        );
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{calendar, date, entry};
+
+    #[test]
+    fn test_render() {
+        let calendar = calendar(vec![
+            entry(date(4, 9), Some(date(4, 13)), "Eid-ul-Fitr, \"tentative\""),
+            entry(date(4, 21), None, "Classes resume"),
+            entry(date(4, 22), None, "=HYPERLINK(\"http://x\")"),
+        ]);
+
+        assert_eq!(
+            render(&calendar),
+            "\u{feff}Start Date,End Date,Weekday,Duration (days),Category,Title\r\n\
+             2024-04-09,2024-04-13,Tuesday-Saturday,5,holiday,\"Eid-ul-Fitr, \"\"tentative\"\"\"\r\n\
+             2024-04-21,2024-04-21,Sunday,1,classes,Classes resume\r\n\
+             2024-04-22,2024-04-22,Monday,1,other,\"'=HYPERLINK(\"\"http://x\"\")\"\r\n"
+        );
+    }
+}
@@ -0,0 +1,179 @@
+//! Converts Gregorian dates to the Bangla calendar (Bangabda), as revised
+//! by Bangla Academy in 2019, and to the tabular Islamic (Hijri) calendar
+
+use chrono::{Datelike, NaiveDate};
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::fmt;
+
+const BANGLA_MONTHS: [&str; 12] = [
+    "Boishakh",
+    "Joishtho",
+    "Asharh",
+    "Shrabon",
+    "Bhadro",
+    "Ashwin",
+    "Kartik",
+    "Ogrohayon",
+    "Poush",
+    "Magh",
+    "Falgun",
+    "Choitro",
+];
+
+const HIJRI_MONTHS: [&str; 12] = [
+    "Muharram",
+    "Safar",
+    "Rabi al-Awwal",
+    "Rabi al-Thani",
+    "Jumada al-Ula",
+    "Jumada al-Akhirah",
+    "Rajab",
+    "Shaban",
+    "Ramadan",
+    "Shawwal",
+    "Dhu al-Qadah",
+    "Dhu al-Hijjah",
+];
+
+/// Julian day number of 1 Muharram 1 AH in the civil tabular calendar
+const HIJRI_EPOCH: i64 = 1948440;
+/// Julian day number of day 0 of the proleptic Gregorian calendar, eg: 0000-12-31
+const CE_EPOCH: i64 = 1721425;
+
+/// A date in a non Gregorian calendar. Months are 1-based
+#[derive(Serialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct ConvertedDate {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+    pub month_name: String,
+}
+
+impl fmt::Display for ConvertedDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.day, self.month_name, self.year)
+    }
+}
+
+fn converted(year: i32, month: u32, day: u32, names: &[&str; 12]) -> ConvertedDate {
+    ConvertedDate {
+        year,
+        month,
+        day,
+        month_name: names[(month - 1) as usize].to_string(),
+    }
+}
+
+/// Formats the days from `start` to `end` in another calendar, eg: 9 - 13 Shawwal 1445
+pub fn format_span(
+    start: NaiveDate,
+    end: NaiveDate,
+    convert: fn(NaiveDate) -> ConvertedDate,
+) -> String {
+    let (start, end) = (convert(start), convert(end));
+    if start == end {
+        start.to_string()
+    } else if (start.year, start.month) == (end.year, end.month) {
+        format!("{} - {end}", start.day)
+    } else {
+        format!("{start} - {end}")
+    }
+}
+
+/// Revised Bangla calendar: the year starts on 14 April, the first six months
+/// have 31 days, Falgun has 29 or 30 in Gregorian leap years, and the rest 30
+pub fn to_bangabda(date: NaiveDate) -> ConvertedDate {
+    let new_year = |year| NaiveDate::from_ymd_opt(year, 4, 14).unwrap_or(date);
+    let (start, year) = if date >= new_year(date.year()) {
+        (new_year(date.year()), date.year() - 593)
+    } else {
+        (new_year(date.year() - 1), date.year() - 594)
+    };
+
+    let falgun_leap = NaiveDate::from_ymd_opt(start.year() + 1, 2, 29).is_some();
+    let mut days = (date - start).num_days() as u32;
+    for month in 1..=12 {
+        let length = match month {
+            1..=6 => 31,
+            11 if falgun_leap => 30,
+            11 => 29,
+            _ => 30,
+        };
+        if days < length {
+            return converted(year, month, days + 1, &BANGLA_MONTHS);
+        }
+        days -= length;
+    }
+
+    // only reachable if a year were longer than its months
+    converted(year, 12, 30, &BANGLA_MONTHS)
+}
+
+/// Arithmetical Islamic calendar with a 30 year cycle of 11 leap years.
+/// Dates may differ by a day or two from ones fixed by moon sighting
+pub fn to_hijri(date: NaiveDate) -> ConvertedDate {
+    let julian_day = date.num_days_from_ce() as i64 + CE_EPOCH;
+
+    let l = julian_day - HIJRI_EPOCH + 10632;
+    let n = (l - 1) / 10631;
+    let l = l - 10631 * n + 354;
+    let j = ((10985 - l) / 5316) * ((50 * l) / 17719) + (l / 5670) * ((43 * l) / 15238);
+    let l = l - ((30 - j) / 15) * ((17719 * j) / 50) - (j / 16) * ((15238 * j) / 43) + 29;
+    let month = (24 * l) / 709;
+    let day = l - (709 * month) / 24;
+    let year = 30 * n + j - 30;
+
+    converted(year as i32, month as u32, day as u32, &HIJRI_MONTHS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_bangabda() {
+        let date = |year, month, day| NaiveDate::from_ymd_opt(year, month, day).unwrap();
+        let bangabda = |year, month, day| to_bangabda(date(year, month, day)).to_string();
+
+        assert_eq!(bangabda(2024, 4, 14), "1 Boishakh 1431");
+        assert_eq!(bangabda(2024, 4, 13), "30 Choitro 1430");
+        assert_eq!(bangabda(2024, 2, 21), "8 Falgun 1430");
+        assert_eq!(bangabda(2024, 3, 26), "12 Choitro 1430");
+        assert_eq!(bangabda(2024, 12, 16), "1 Poush 1431");
+        // Falgun has 30 days in 2024, a leap year, and 29 in 2025
+        assert_eq!(bangabda(2024, 3, 14), "30 Falgun 1430");
+        assert_eq!(bangabda(2025, 3, 14), "29 Falgun 1431");
+        assert_eq!(bangabda(2025, 3, 15), "1 Choitro 1431");
+    }
+
+    #[test]
+    fn test_format_span() {
+        let date = |month, day| NaiveDate::from_ymd_opt(2024, month, day).unwrap();
+
+        assert_eq!(
+            format_span(date(4, 10), date(4, 14), to_hijri),
+            "1 - 5 Shawwal 1445"
+        );
+        assert_eq!(
+            format_span(date(4, 10), date(4, 14), to_bangabda),
+            "27 Choitro 1430 - 1 Boishakh 1431"
+        );
+    }
+
+    #[test]
+    fn test_to_hijri() {
+        let hijri = |month, day| to_hijri(NaiveDate::from_ymd_opt(2024, month, day).unwrap());
+
+        assert_eq!(hijri(3, 11).to_string(), "1 Ramadan 1445");
+        assert_eq!(
+            hijri(4, 10),
+            ConvertedDate {
+                year: 1445,
+                month: 10,
+                day: 1,
+                month_name: "Shawwal".to_string()
+            }
+        );
+    }
+}
@@ -50,8 +50,10 @@ pub fn parse_date_part() -> impl Parser<char, DatePart, Error = Simple<char>> {
     parse_month()
         .or_not()
         .then(
-            take_until(text::digits(10))
-                .map(|(_, day): (Vec<char>, String)| day.parse::<u8>().unwrap()),
+            take_until(text::digits(10)).try_map(|(_, day): (Vec<char>, String), span| {
+                day.parse::<u8>()
+                    .map_err(|e| Simple::custom(span, e.to_string()))
+            }),
         )
         .map(|(month, day)| DatePart { month, day })
 }
@@ -101,7 +103,9 @@ pub fn parse_date_parts<'a>(i: &'a str) -> IResult<&'a str, Vec<DatePart>> {
 
 /// converts date parts into a readable struct
 pub fn parse_date_range(i: &str) -> Result<DateRange, Box<dyn Error>> {
-    let date_parts = parse_date_parts().parse(i).expect("Couldn't parse date");
+    let date_parts = parse_date_parts()
+        .parse(i)
+        .map_err(|_| format!("Couldn't parse date: {i}"))?;
 
     if date_parts.len() > 2 || date_parts.is_empty() {
         return Err("Invalid date range".into());
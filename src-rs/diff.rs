@@ -0,0 +1,87 @@
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::parser::{CalendarDetails, Entry};
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Rescheduled {
+    pub event: String,
+    pub old_date: (NaiveDate, Option<NaiveDate>),
+    pub new_date: (NaiveDate, Option<NaiveDate>),
+}
+
+#[derive(Serialize, Debug, Default, PartialEq)]
+pub struct DiffSummary {
+    pub added: Vec<Entry>,
+    pub removed: Vec<Entry>,
+    pub rescheduled: Vec<Rescheduled>,
+}
+
+impl DiffSummary {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.rescheduled.is_empty()
+    }
+}
+
+/// Compares entries of two revisions of a calendar.
+/// Entries are matched by their event text, so an event whose date
+/// moved is reported as rescheduled rather than removed and added
+pub fn diff_calendars(old: &CalendarDetails, new: &CalendarDetails) -> DiffSummary {
+    let mut summary = DiffSummary::default();
+    let mut unmatched_old: Vec<&Entry> = old.entries.iter().collect();
+
+    for entry in &new.entries {
+        match unmatched_old.iter().position(|x| x.event == entry.event) {
+            Some(index) => {
+                let old_entry = unmatched_old.remove(index);
+                if old_entry.date != entry.date {
+                    summary.rescheduled.push(Rescheduled {
+                        event: entry.event.clone(),
+                        old_date: old_entry.date,
+                        new_date: entry.date,
+                    });
+                }
+            }
+            None => summary.added.push(entry.clone()),
+        }
+    }
+
+    summary.removed = unmatched_old.into_iter().cloned().collect();
+
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{date, entry};
+
+    fn calendar(entries: Vec<(u32, &str)>) -> CalendarDetails {
+        crate::test_util::calendar(
+            entries
+                .into_iter()
+                .map(|(day, event)| entry(date(1, day), None, event))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_diff_calendars() {
+        let old = calendar(vec![(1, "Classes begin"), (5, "Add/Drop"), (9, "Holiday")]);
+        let new = calendar(vec![
+            (1, "Classes begin"),
+            (6, "Add/Drop"),
+            (20, "Mid-term"),
+        ]);
+
+        let summary = diff_calendars(&old, &new);
+
+        assert_eq!(summary.added.len(), 1);
+        assert_eq!(summary.added[0].event, "Mid-term");
+        assert_eq!(summary.removed.len(), 1);
+        assert_eq!(summary.removed[0].event, "Holiday");
+        assert_eq!(summary.rescheduled.len(), 1);
+        assert_eq!(summary.rescheduled[0].event, "Add/Drop");
+        assert!(diff_calendars(&old, &old).is_empty());
+    }
+}
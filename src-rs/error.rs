@@ -0,0 +1,104 @@
+use reqwest::header::{ALLOW, CONTENT_TYPE};
+use serde_json::json;
+use std::error::Error;
+use std::fmt;
+use vercel_runtime::{Body, Error as VercelError, Response, StatusCode};
+
+/// Errors surfaced to API clients as RFC 7807 problem details
+#[derive(Debug, PartialEq)]
+pub enum ApiError {
+    /// The request is missing or has a malformed parameter
+    BadRequest(String),
+    /// ewubd.edu couldn't be reached or responded with an error
+    Upstream(String),
+    /// The calendar page was fetched but couldn't be parsed
+    Unprocessable(String),
+    /// The endpoint doesn't take the request's method, only those in `allow`
+    MethodNotAllowed { allow: &'static str, detail: String },
+}
+
+impl ApiError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::Upstream(_) => StatusCode::BAD_GATEWAY,
+            ApiError::Unprocessable(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::MethodNotAllowed { .. } => StatusCode::METHOD_NOT_ALLOWED,
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            ApiError::BadRequest(_) => "Invalid request",
+            ApiError::Upstream(_) => "Couldn't reach ewubd.edu",
+            ApiError::Unprocessable(_) => "Couldn't read calendar",
+            ApiError::MethodNotAllowed { .. } => "Method not allowed",
+        }
+    }
+
+    pub fn detail(&self) -> &str {
+        match self {
+            ApiError::BadRequest(detail)
+            | ApiError::Upstream(detail)
+            | ApiError::Unprocessable(detail)
+            | ApiError::MethodNotAllowed { detail, .. } => detail,
+        }
+    }
+
+    pub fn to_response(&self) -> Result<Response<Body>, VercelError> {
+        let status = self.status();
+        let problem = json!({
+            "type": "about:blank",
+            "title": self.title(),
+            "status": status.as_u16(),
+            "detail": self.detail(),
+        });
+
+        let mut resp = Response::builder()
+            .status(status)
+            .header(CONTENT_TYPE, "application/problem+json");
+        if let ApiError::MethodNotAllowed { allow, .. } = self {
+            resp = resp.header(ALLOW, *allow);
+        }
+        Ok(resp.body(problem.to_string().into())?)
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.title(), self.detail())
+    }
+}
+
+impl Error for ApiError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_problem_response() {
+        let resp = ApiError::Upstream("timed out".to_string())
+            .to_response()
+            .unwrap();
+
+        assert_eq!(resp.status(), StatusCode::BAD_GATEWAY);
+        assert_eq!(resp.headers()[CONTENT_TYPE], "application/problem+json");
+
+        let body: serde_json::Value = match resp.body() {
+            Body::Text(text) => serde_json::from_str(text).unwrap(),
+            _ => panic!("expected a text body"),
+        };
+        assert_eq!(body["status"], 502);
+        assert_eq!(body["detail"], "timed out");
+
+        let resp = ApiError::MethodNotAllowed {
+            allow: "POST",
+            detail: "Sections must be POSTed".to_string(),
+        }
+        .to_response()
+        .unwrap();
+        assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(resp.headers()[ALLOW], "POST");
+    }
+}
@@ -0,0 +1,548 @@
+//! Midterm and final exam schedules: typed exam slots parsed from the schedule pages
+//! and personal exam calendars for the courses a student takes
+
+use chrono::{prelude::*, Days};
+use ics::{
+    components::{Parameter, Property},
+    escape_text,
+    properties::{Description, DtEnd, DtStart, Geo, Location, Summary, Transp, Trigger, URL},
+    Alarm, Event,
+};
+use regex::Regex;
+use scraper::{ElementRef, Html};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::error::Error;
+use std::sync::OnceLock;
+
+use crate::category::Category;
+use crate::error::ApiError;
+use crate::options::IcsOptions;
+use crate::parser::{local_date_time, new_calendar, to_ics, with_timezone};
+use crate::routine::parse_time;
+use crate::scraper_profile::{ExamHeaders, ExamProfile, ScraperProfile};
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ExamSchedule {
+    /// Title of the schedule, eg: Final Exam Schedule Spring 2024
+    pub name: String,
+    pub semester: String,
+    pub year: i32,
+    pub slots: Vec<ExamSlot>,
+    /// Rows left out as their date couldn't be read, eg: CSE110 on TBA
+    pub skipped: Vec<String>,
+}
+
+/// Courses sitting an exam together
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ExamSlot {
+    /// Course codes, eg: CSE110
+    pub courses: Vec<String>,
+    pub date: NaiveDate,
+    /// Time or slot as written on the schedule, eg: 9:00 AM - 11:00 AM
+    pub slot: String,
+    pub start: Option<NaiveTime>,
+    pub end: Option<NaiveTime>,
+    pub room: Option<String>,
+}
+
+/// Columns of a schedule table, found from its header row
+#[derive(Debug)]
+struct Columns {
+    date: usize,
+    courses: usize,
+    slot: Option<usize>,
+    room: Option<usize>,
+}
+
+impl Columns {
+    fn from_header(cells: &[String], headers: &ExamHeaders) -> Option<Self> {
+        let find = |keywords: &[String]| {
+            cells.iter().position(|cell| {
+                let cell = cell.to_lowercase();
+                keywords.iter().any(|x| cell.contains(&x.to_lowercase()))
+            })
+        };
+
+        Some(Columns {
+            date: find(&headers.date)?,
+            courses: find(&headers.courses)?,
+            slot: find(&headers.slot),
+            room: find(&headers.room),
+        })
+    }
+}
+
+/// Uppercase course code without spaces or dashes, eg: cse 110 is CSE110
+pub fn normalize_course(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_uppercase()
+}
+
+fn parse_courses(text: &str, profile: &ExamProfile) -> Vec<String> {
+    profile
+        .course_pattern
+        .find_iter(text)
+        .map(|x| normalize_course(x.as_str()))
+        .collect()
+}
+
+/// Parses a date with or without its year and weekday, eg: Sunday, 12 May 2024 or May 12.
+/// Dates without a year before the semester's first month are in the next year,
+/// eg: January finals of a fall semester
+fn parse_exam_date(text: &str, semester: &str, year: i32) -> Option<NaiveDate> {
+    static NOISE: OnceLock<Regex> = OnceLock::new();
+    let noise_regex = NOISE.get_or_init(|| {
+        Regex::new(
+            r"(?i)\b(sun|mon|tue|tues|wed|thu|thurs|fri|sat)(day|nesday|rsday|urday)?\b|[(),]",
+        )
+        .unwrap()
+    });
+    static ORDINAL: OnceLock<Regex> = OnceLock::new();
+    let ordinal_regex = ORDINAL.get_or_init(|| Regex::new(r"(\d)(st|nd|rd|th)\b").unwrap());
+
+    let text = noise_regex.replace_all(text, " ");
+    let text = ordinal_regex.replace_all(&text, "$1");
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    let with_year = [
+        "%d %B %Y", "%B %d %Y", "%d-%m-%Y", "%d/%m/%Y", "%d.%m.%Y", "%Y-%m-%d",
+    ];
+    let without_year = ["%d %B %Y", "%B %d %Y"];
+    with_year
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(&text, format).ok())
+        .or_else(|| {
+            let text = format!("{text} {year}");
+            let date = without_year
+                .iter()
+                .find_map(|format| NaiveDate::parse_from_str(&text, format).ok())?;
+            let first_month = match semester.to_lowercase().as_str() {
+                "summer" => 5,
+                "fall" => 9,
+                _ => 1,
+            };
+            if date.month() < first_month {
+                date.with_year(year + 1)
+            } else {
+                Some(date)
+            }
+        })
+}
+
+/// Parses a time range, eg: 9:00 AM - 11:00 AM or 9.00 - 11.00 am.
+/// A start without AM or PM is taken to be in the same half of the day as the end, if it can
+fn parse_time_range(text: &str) -> Option<(NaiveTime, NaiveTime)> {
+    static RANGE: OnceLock<Regex> = OnceLock::new();
+    let range_regex = RANGE.get_or_init(|| {
+        Regex::new(
+            r"(?i)(\d{1,2})[:.](\d{2})\s*([ap])?\.?m?\.?\s*(?:-|–|to)\s*(\d{1,2})[:.](\d{2})\s*([ap])?\.?m?\.?",
+        )
+        .unwrap()
+    });
+    let captures = range_regex.captures(text)?;
+
+    let time = |hour: usize, minute: usize, meridiem: Option<&str>| {
+        let meridiem = meridiem.map(|x| format!(" {}M", x.to_uppercase()));
+        parse_time(&format!(
+            "{}:{}{}",
+            &captures[hour],
+            &captures[minute],
+            meridiem.unwrap_or_default()
+        ))
+        .ok()
+    };
+
+    let end_meridiem = captures.get(6).map(|x| x.as_str());
+    let end = time(4, 5, end_meridiem)?;
+    let start = match captures.get(3) {
+        Some(meridiem) => time(1, 2, Some(meridiem.as_str()))?,
+        None => time(1, 2, end_meridiem)
+            .filter(|start| *start < end)
+            .or_else(|| time(1, 2, Some("a")))?,
+    };
+
+    (start < end).then_some((start, end))
+}
+
+/// Most columns a cell can span, as in HTML
+const MAX_COLSPAN: usize = 1000;
+
+/// Texts of a row's cells by column. A cell fills as many columns as its colspan, and
+/// with a rowspan, `spanning` carries it down to the same columns of the rows below
+fn cell_texts(
+    row: ElementRef,
+    profile: &ExamProfile,
+    spanning: &mut Vec<Option<(String, usize)>>,
+) -> Vec<String> {
+    let mut own = row.select(&profile.cell);
+    let mut cells = vec![];
+
+    loop {
+        let column = cells.len();
+        if let Some(Some((text, rows))) = spanning.get_mut(column) {
+            cells.push(text.clone());
+            *rows -= 1;
+            if *rows == 0 {
+                spanning[column] = None;
+            }
+            continue;
+        }
+
+        let Some(cell) = own.next() else {
+            // a cell spanning from above may still be further along the row
+            if spanning.iter().skip(column).any(Option::is_some) {
+                cells.push(String::new());
+                continue;
+            }
+            break;
+        };
+        let text = cell.text().collect::<Vec<_>>().join(" ");
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        let span = |name| {
+            cell.value()
+                .attr(name)
+                .and_then(|x| x.trim().parse::<usize>().ok())
+                .filter(|x| *x > 0)
+                .unwrap_or(1)
+        };
+        let rows = span("rowspan");
+
+        for _ in 0..span("colspan").min(MAX_COLSPAN) {
+            if rows > 1 {
+                let column = cells.len();
+                if spanning.len() <= column {
+                    spanning.resize(column + 1, None);
+                }
+                spanning[column] = Some((text.clone(), rows - 1));
+            }
+            cells.push(text.clone());
+        }
+    }
+
+    cells
+}
+
+/// Parses an exam schedule page. Each table needs a header row naming its date
+/// and course columns; cells are placed in the columns and rows their colspan and
+/// rowspan cover. Rows with a date that can't be read, eg: TBA, are skipped
+pub fn parse_exam_schedule<'a>(doc: &'a Html) -> Result<ExamSchedule, Box<dyn Error + 'a>> {
+    let page = &ScraperProfile::global()?.calendar;
+    let profile = &ScraperProfile::global()?.exam_schedule;
+
+    let raw_doc = doc
+        .select(&page.content)
+        .next()
+        .ok_or("Couldn't extract raw document")?
+        .text()
+        .collect::<String>();
+    let semester_capture = page
+        .semester_pattern
+        .captures(&raw_doc)
+        .ok_or("Semester not found")?;
+    let semester = semester_capture
+        .get(1)
+        .ok_or("Semester not found")?
+        .as_str()
+        .to_string();
+    let year = semester_capture
+        .get(2)
+        .ok_or("Semester year not found")?
+        .as_str()
+        .parse::<i32>()
+        .map_err(|_| "Couldn't decode year")?;
+
+    let name = doc
+        .select(&page.name)
+        .map(|el| el.text().collect::<String>().trim().to_owned())
+        .collect::<String>();
+
+    let mut slots = vec![];
+    let mut skipped = vec![];
+    for table in doc.select(&profile.table) {
+        let mut columns = None;
+        let mut spanning = vec![];
+
+        for row in table.select(&profile.row) {
+            let cells = cell_texts(row, profile, &mut spanning);
+            let Some(columns) = &columns else {
+                columns = Columns::from_header(&cells, &profile.headers);
+                continue;
+            };
+
+            let cell = |index: usize| cells.get(index).map(String::as_str).unwrap_or_default();
+            let courses = parse_courses(cell(columns.courses), profile);
+            if courses.is_empty() {
+                continue;
+            }
+
+            let date_text = cell(columns.date);
+            let Some(date) = parse_exam_date(date_text, &semester, year) else {
+                skipped.push(format!("{} on {date_text}", courses.join(", ")));
+                continue;
+            };
+            let slot = columns.slot.map(cell).unwrap_or_default().to_owned();
+            let times = parse_time_range(&slot);
+
+            slots.push(ExamSlot {
+                courses,
+                date,
+                start: times.map(|x| x.0),
+                end: times.map(|x| x.1),
+                slot,
+                room: columns
+                    .room
+                    .map(cell)
+                    .filter(|x| !x.is_empty())
+                    .map(str::to_owned),
+            });
+        }
+    }
+
+    if slots.is_empty() {
+        return Err("No exam slots found".into());
+    }
+
+    Ok(ExamSchedule {
+        name,
+        semester,
+        year,
+        slots,
+        skipped,
+    })
+}
+
+impl ExamSchedule {
+    /// Exams of the courses, as pairs of a course and its slot
+    pub fn exams_of<'a>(&'a self, courses: &[String]) -> Vec<(&'a str, &'a ExamSlot)> {
+        let courses = courses
+            .iter()
+            .map(|x| normalize_course(x))
+            .collect::<HashSet<_>>();
+
+        self.slots
+            .iter()
+            .flat_map(|slot| {
+                slot.courses
+                    .iter()
+                    .map(move |course| (course.as_str(), slot))
+            })
+            .filter(|(course, _)| courses.contains(*course))
+            .collect()
+    }
+
+    /// Kind of exams the schedule is for, used to title them
+    fn exam_kind(&self) -> &'static str {
+        let name = self.name.to_lowercase();
+        if name.contains("mid") {
+            "Midterm exam"
+        } else if name.contains("final") {
+            "Final exam"
+        } else {
+            "Exam"
+        }
+    }
+}
+
+/// Generates a calendar of the exams of the courses a student takes
+pub fn generate_exam_ics(
+    schedule: &ExamSchedule,
+    courses: &[String],
+    options: &IcsOptions,
+) -> Result<String, ApiError> {
+    let exams = schedule.exams_of(courses);
+    if exams.is_empty() {
+        return Err(ApiError::BadRequest(
+            "No exams found for the requested courses".to_string(),
+        ));
+    }
+
+    let cal_name = format!("{} {} exams", schedule.semester, schedule.year);
+    let cal_description = format!("Exams of {} from the {}", courses.join(", "), schedule.name);
+    let mut calendar = new_calendar(&cal_name, &cal_description, options);
+
+    for (course, slot) in exams {
+        let ev_hash = xxhash_rust::xxh3::xxh3_64(
+            format!("{} {course} {} {}", schedule.name, slot.date, slot.slot).as_bytes(),
+        );
+        let mut event = Event::new(
+            format!("{:x}", ev_hash),
+            Utc::now().format("%Y%m%dT000000Z").to_string(),
+        );
+
+        match slot.start.zip(slot.end) {
+            Some((start, end)) => {
+                event.push(with_timezone(DtStart::new(local_date_time(
+                    slot.date, start,
+                ))));
+                event.push(with_timezone(DtEnd::new(local_date_time(slot.date, end))));
+            }
+            None => {
+                let mut dtstart = DtStart::new(slot.date.format("%Y%m%d").to_string());
+                dtstart.add(Parameter::new("VALUE", "DATE"));
+                event.push(dtstart);
+                let mut dtend = DtEnd::new((slot.date + Days::new(1)).format("%Y%m%d").to_string());
+                dtend.add(Parameter::new("VALUE", "DATE"));
+                event.push(dtend);
+            }
+        }
+
+        if !slot.slot.is_empty() {
+            event.push(Description::new(escape_text(format!(
+                "Slot: {}",
+                slot.slot
+            ))));
+        }
+        if let Some(source_url) = &options.source_url {
+            event.push(URL::new(source_url));
+        }
+        let location = slot
+            .room
+            .as_deref()
+            .or(options.location_for(Category::Exam));
+        if let Some(location) = location {
+            event.push(Location::new(escape_text(location)));
+            if let Some((latitude, longitude)) = options.geo {
+                event.push(Geo::new(format!("{latitude:.6};{longitude:.6}")));
+            }
+        }
+
+        event.push(Transp::new("OPAQUE"));
+        event.push(Property::new("X-MICROSOFT-CDO-BUSYSTATUS", "BUSY"));
+
+        let title = format!("{course} {}", schedule.exam_kind());
+        for reminder in options.alarms_for(Category::Exam) {
+            event.add_alarm(Alarm::display(
+                Trigger::new(reminder.trigger()),
+                Description::new(escape_text(title.clone())),
+            ));
+        }
+
+        event.push(Summary::new(escape_text(title)));
+        calendar.add_event(event);
+    }
+
+    Ok(to_ics(calendar))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conformance::validate;
+
+    const PAGE: &str = r#"
+        <div class="row"><div class="col-md-9">
+            <h3>Final Exam Schedule Spring 2024</h3>
+            <table>
+                <tr><th>Date</th><th>Time</th><th>Course Code</th><th>Room</th></tr>
+                <tr>
+                    <td rowspan="2">Sunday, 12 May</td>
+                    <td>9:00 - 11:00 AM</td>
+                    <td>CSE110, CSE 106</td>
+                    <td>AB1-101</td>
+                </tr>
+                <tr><td>1:30 PM - 3:30 PM</td><td>MAT101</td><td></td></tr>
+                <tr><td>14 May 2024</td><td>Slot 3</td><td>ENG-102</td><td></td></tr>
+                <tr><td>14 May 2024</td><td>Slot 4</td><td>ENG-102</td><td></td></tr>
+                <tr><td>15 May 2024</td><td colspan="3">Reserved for make-up exams</td></tr>
+                <tr><td>TBA</td><td>Slot 1</td><td>PHY101</td><td rowspan="2">AB2-201</td></tr>
+                <tr><td>16 May 2024</td><td>Slot 2</td><td>CHE109</td></tr>
+            </table>
+        </div></div>"#;
+
+    #[test]
+    fn test_parse_exam_schedule() {
+        let schedule = parse_exam_schedule(&Html::parse_document(PAGE)).unwrap();
+        let date = |day| NaiveDate::from_ymd_opt(2024, 5, day).unwrap();
+        let time = |hour, minute| NaiveTime::from_hms_opt(hour, minute, 0);
+
+        assert_eq!(schedule.name, "Final Exam Schedule Spring 2024");
+        assert_eq!(
+            (schedule.semester.as_str(), schedule.year),
+            ("Spring", 2024)
+        );
+        assert_eq!(
+            schedule.slots,
+            vec![
+                ExamSlot {
+                    courses: vec!["CSE110".to_string(), "CSE106".to_string()],
+                    date: date(12),
+                    slot: "9:00 - 11:00 AM".to_string(),
+                    start: time(9, 0),
+                    end: time(11, 0),
+                    room: Some("AB1-101".to_string()),
+                },
+                ExamSlot {
+                    courses: vec!["MAT101".to_string()],
+                    date: date(12),
+                    slot: "1:30 PM - 3:30 PM".to_string(),
+                    start: time(13, 30),
+                    end: time(15, 30),
+                    room: None,
+                },
+                ExamSlot {
+                    courses: vec!["ENG102".to_string()],
+                    date: date(14),
+                    slot: "Slot 3".to_string(),
+                    start: None,
+                    end: None,
+                    room: None,
+                },
+                ExamSlot {
+                    courses: vec!["ENG102".to_string()],
+                    date: date(14),
+                    slot: "Slot 4".to_string(),
+                    start: None,
+                    end: None,
+                    room: None,
+                },
+                // the room is merged from the row above, not the leading date
+                ExamSlot {
+                    courses: vec!["CHE109".to_string()],
+                    date: date(16),
+                    slot: "Slot 2".to_string(),
+                    start: None,
+                    end: None,
+                    room: Some("AB2-201".to_string()),
+                },
+            ]
+        );
+        assert_eq!(schedule.skipped, vec!["PHY101 on TBA"]);
+
+        let date = |year, month, day| NaiveDate::from_ymd_opt(year, month, day);
+        assert_eq!(parse_exam_date("Jan 5th", "Fall", 2024), date(2025, 1, 5));
+        assert_eq!(
+            parse_exam_date("(Thursday) 19/12/2024", "Fall", 2024),
+            date(2024, 12, 19)
+        );
+    }
+
+    #[test]
+    fn test_generate_exam_ics() {
+        let schedule = parse_exam_schedule(&Html::parse_document(PAGE)).unwrap();
+        let courses = vec!["cse-106".to_string(), "ENG102".to_string()];
+
+        let ics = generate_exam_ics(&schedule, &courses, &IcsOptions::default()).unwrap();
+
+        assert_eq!(validate(ics.as_bytes()), vec![]);
+        let lines = ics.lines().collect::<Vec<_>>();
+        assert!(lines.contains(&"SUMMARY:CSE106 Final exam"));
+        assert!(lines.contains(&"DTSTART;TZID=Asia/Dhaka:20240512T090000"));
+        assert!(lines.contains(&"LOCATION:AB1-101"));
+        assert!(lines.contains(&"SUMMARY:ENG102 Final exam"));
+        assert!(lines.contains(&"DTSTART;VALUE=DATE:20240514"));
+        // both ENG102 exams on the 14th keep their own UID
+        let uids = lines
+            .iter()
+            .filter(|x| x.starts_with("UID:"))
+            .collect::<HashSet<_>>();
+        assert_eq!(uids.len(), 3);
+        assert!(!ics.contains("CSE110"));
+
+        let error = generate_exam_ics(&schedule, &["PHY101".to_string()], &IcsOptions::default())
+            .unwrap_err();
+        assert_eq!(error.detail(), "No exams found for the requested courses");
+    }
+}
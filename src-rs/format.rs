@@ -0,0 +1,118 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::ApiError;
+use crate::options::IcsOptions;
+use crate::parser::{generate_ics_with_options, CalendarDetails};
+use crate::{csv, jcal, render, xcal};
+
+/// Renderings of a calendar offered by the generate endpoint and the cli
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    #[default]
+    Ics,
+    JCal,
+    XCal,
+    Csv,
+    Markdown,
+    Html,
+    Agenda,
+}
+
+impl OutputFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            OutputFormat::Ics => "text/calendar",
+            OutputFormat::JCal => "application/calendar+json",
+            OutputFormat::XCal => "application/calendar+xml",
+            OutputFormat::Csv => "text/csv; charset=utf-8",
+            OutputFormat::Markdown => "text/markdown; charset=utf-8",
+            OutputFormat::Html => "text/html; charset=utf-8",
+            OutputFormat::Agenda => "text/plain; charset=utf-8",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Ics => "ics",
+            OutputFormat::JCal => "jcal.json",
+            OutputFormat::XCal => "xcs",
+            OutputFormat::Csv => "csv",
+            OutputFormat::Markdown => "md",
+            OutputFormat::Html => "html",
+            OutputFormat::Agenda => "txt",
+        }
+    }
+
+    /// How browsers should treat a response: the readable formats are shown in place,
+    /// the rest are saved as files
+    pub fn disposition(&self) -> &'static str {
+        match self {
+            OutputFormat::Markdown | OutputFormat::Html | OutputFormat::Agenda => "inline",
+            _ => "attachment",
+        }
+    }
+
+    /// Renders the calendar. Options only apply to the iCalendar based formats
+    pub fn render(
+        &self,
+        calendar: CalendarDetails,
+        options: &IcsOptions,
+    ) -> Result<String, ApiError> {
+        let ics = |calendar| generate_ics_with_options(calendar, options);
+        let converted = match self {
+            OutputFormat::Ics => return Ok(ics(calendar)),
+            OutputFormat::Csv => return Ok(csv::render(&calendar)),
+            OutputFormat::Markdown => return Ok(render::markdown(&calendar)),
+            OutputFormat::Html => return Ok(render::html(&calendar)),
+            OutputFormat::Agenda => return Ok(render::agenda(&calendar)),
+            OutputFormat::JCal => jcal::from_ics(&ics(calendar)).map(|x| x.to_string()),
+            OutputFormat::XCal => xcal::from_ics(&ics(calendar)),
+        };
+        converted.map_err(|e| ApiError::Unprocessable(e.to_string()))
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "ics" | "ical" | "icalendar" => Ok(OutputFormat::Ics),
+            "jcal" => Ok(OutputFormat::JCal),
+            "xcal" => Ok(OutputFormat::XCal),
+            "csv" => Ok(OutputFormat::Csv),
+            "md" | "markdown" => Ok(OutputFormat::Markdown),
+            "html" => Ok(OutputFormat::Html),
+            "txt" | "text" | "agenda" => Ok(OutputFormat::Agenda),
+            _ => Err(ApiError::BadRequest(format!("Unknown format \"{s}\""))),
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            OutputFormat::Ics => "ics",
+            OutputFormat::JCal => "jcal",
+            OutputFormat::XCal => "xcal",
+            OutputFormat::Csv => "csv",
+            OutputFormat::Markdown => "markdown",
+            OutputFormat::Html => "html",
+            OutputFormat::Agenda => "agenda",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disposition() {
+        assert_eq!(OutputFormat::Html.disposition(), "inline");
+        assert_eq!(OutputFormat::Agenda.disposition(), "inline");
+        assert_eq!(OutputFormat::Ics.disposition(), "attachment");
+        assert_eq!(OutputFormat::Csv.disposition(), "attachment");
+    }
+}
@@ -0,0 +1,178 @@
+//! Bangladesh public holidays, bundled so checks work offline,
+//! and a cross-check of academic calendars against them.
+//! Dates of lunar holidays are the ones announced in the government's
+//! holiday list, so they can be a day off from the observed ones
+//!
+//! Updating `data/bd_public_holidays.json`: the Cabinet Division publishes the next year's
+//! holiday list late each year. Add the year's general holidays, not the optional or
+//! executive order ones, under the year as a key, with an `end` for multi day holidays.
+//! Names are matched against the English text of calendars, so reuse the existing ones.
+//! Until a year is added, checks of its calendars report it in `unknown_years`.
+//! Lunar dates should be corrected after the moon sighting when they move
+
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+use crate::category::Category;
+use crate::parser::{CalendarDetails, Entry};
+
+const DATASET: &str = include_str!("data/bd_public_holidays.json");
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct PublicHoliday {
+    pub name: String,
+    pub start: NaiveDate,
+    /// Last day of multi day holidays, inclusive
+    #[serde(default)]
+    pub end: Option<NaiveDate>,
+}
+
+impl PublicHoliday {
+    pub fn end(&self) -> NaiveDate {
+        self.end.unwrap_or(self.start)
+    }
+
+    fn overlaps(&self, start: NaiveDate, end: NaiveDate) -> bool {
+        self.start <= end && start <= self.end()
+    }
+}
+
+/// Public holidays by year
+pub fn public_holidays() -> &'static BTreeMap<i32, Vec<PublicHoliday>> {
+    static HOLIDAYS: OnceLock<BTreeMap<i32, Vec<PublicHoliday>>> = OnceLock::new();
+    HOLIDAYS.get_or_init(|| {
+        serde_json::from_str(DATASET).expect("bundled public holiday dataset is valid")
+    })
+}
+
+/// Public holidays falling on an entry, if the entry is a holiday
+pub fn official_holidays(entry: &Entry) -> Vec<&'static PublicHoliday> {
+    if Category::classify(&entry.event) != Category::Holiday {
+        return vec![];
+    }
+
+    (entry.start().year()..=entry.end().year())
+        .filter_map(|year| public_holidays().get(&year))
+        .flatten()
+        .filter(|holiday| holiday.overlaps(entry.start(), entry.end()))
+        .collect()
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct MatchedHoliday {
+    pub event: String,
+    pub holidays: Vec<String>,
+}
+
+#[derive(Serialize, Debug, Default, PartialEq)]
+pub struct HolidayCheck {
+    /// Public holidays within the semester that no holiday on the calendar covers
+    pub missing: Vec<PublicHoliday>,
+    /// Calendar holidays falling on public holidays
+    pub matched: Vec<MatchedHoliday>,
+    /// Years of the semester the dataset has no holidays for
+    pub unknown_years: Vec<i32>,
+}
+
+/// Compares the holidays of a calendar with the public holidays
+/// between its first and last events
+pub fn check(calendar: &CalendarDetails) -> HolidayCheck {
+    let mut result = HolidayCheck::default();
+
+    let first = calendar.entries.iter().map(Entry::start).min();
+    let last = calendar.entries.iter().map(Entry::end).max();
+    let (Some(first), Some(last)) = (first, last) else {
+        return result;
+    };
+
+    let mut covered = vec![];
+    for entry in &calendar.entries {
+        let holidays = official_holidays(entry);
+        covered.extend(holidays.iter().copied());
+        if !holidays.is_empty() {
+            result.matched.push(MatchedHoliday {
+                event: entry.event.clone(),
+                holidays: holidays.iter().map(|x| x.name.clone()).collect(),
+            });
+        }
+    }
+
+    for year in first.year()..=last.year() {
+        let Some(holidays) = public_holidays().get(&year) else {
+            result.unknown_years.push(year);
+            continue;
+        };
+        let missing = holidays
+            .iter()
+            .filter(|holiday| holiday.overlaps(first, last) && !covered.contains(holiday));
+        result.missing.extend(missing.cloned());
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{calendar, date, entry};
+
+    #[test]
+    fn test_check() {
+        let calendar = calendar(vec![
+            entry(date(1, 7), None, "Classes begin"),
+            entry(date(2, 21), None, "Shaheed Dibosh (Holiday)"),
+            entry(date(3, 26), None, "Mid-term Examination"),
+            entry(
+                date(4, 9),
+                Some(date(4, 14)),
+                "Eid-ul-Fitr and Pohela Boishakh (University closed)",
+            ),
+            entry(date(4, 25), None, "Last day of classes"),
+        ]);
+
+        let result = check(&calendar);
+
+        assert_eq!(
+            result.matched,
+            vec![
+                MatchedHoliday {
+                    event: "Shaheed Dibosh (Holiday)".to_string(),
+                    holidays: vec!["Shaheed Day and International Mother Language Day".to_string()],
+                },
+                MatchedHoliday {
+                    event: "Eid-ul-Fitr and Pohela Boishakh (University closed)".to_string(),
+                    holidays: vec!["Eid-ul-Fitr".to_string(), "Pohela Boishakh".to_string()],
+                },
+            ]
+        );
+        let missing = result
+            .missing
+            .iter()
+            .map(|x| x.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            missing,
+            vec![
+                "Shab-e-Barat",
+                "Birthday of Bangabandhu and National Children's Day",
+                "Independence Day",
+                "Jumatul Bidah",
+                "Shab-e-Qadr",
+            ]
+        );
+        assert!(result.unknown_years.is_empty());
+    }
+
+    #[test]
+    fn test_dataset() {
+        for (year, holidays) in public_holidays() {
+            for holiday in holidays {
+                assert_eq!(holiday.start.year(), *year, "{holiday:?}");
+                assert!(holiday.end() >= holiday.start, "{holiday:?}");
+            }
+        }
+        assert!(public_holidays().contains_key(&2026));
+    }
+}
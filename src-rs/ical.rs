@@ -0,0 +1,379 @@
+//! A minimal reader for iCalendar (RFC 5545) text,
+//! enough to walk calendars produced by `generate_ics`
+
+use std::error::Error;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Property {
+    pub name: String,
+    pub params: Vec<(String, String)>,
+    /// Raw value, still escaped if it is text
+    pub value: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Component {
+    pub name: String,
+    pub properties: Vec<Property>,
+    pub components: Vec<Component>,
+}
+
+/// A property value in one of the value types of RFC 5545 section 3.3
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Text(String),
+    Date(String),
+    DateTime(String),
+    UtcOffset(String),
+    Duration(String),
+    Uri(String),
+    CalAddress(String),
+    Integer(i64),
+    Float(f64),
+    /// rule parts of a RRULE, eg: ("freq", ["WEEKLY"]), ("byday", ["SU", "TU"])
+    Recur(Vec<(String, Vec<String>)>),
+    Unknown(String),
+}
+
+impl Value {
+    /// Name of the value type, as used by jCal and xCal
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Text(_) => "text",
+            Value::Date(_) => "date",
+            Value::DateTime(_) => "date-time",
+            Value::UtcOffset(_) => "utc-offset",
+            Value::Duration(_) => "duration",
+            Value::Uri(_) => "uri",
+            Value::CalAddress(_) => "cal-address",
+            Value::Integer(_) => "integer",
+            Value::Float(_) => "float",
+            Value::Recur(_) => "recur",
+            Value::Unknown(_) => "unknown",
+        }
+    }
+}
+
+impl Component {
+    pub fn property(&self, name: &str) -> Option<&Property> {
+        self.properties
+            .iter()
+            .find(|prop| prop.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn components_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Component> {
+        self.components
+            .iter()
+            .filter(move |component| component.name.eq_ignore_ascii_case(name))
+    }
+}
+
+impl Property {
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Text value with escapes removed
+    pub fn text(&self) -> String {
+        unescape_text(&self.value)
+    }
+
+    /// Interprets the raw value using the `VALUE` parameter,
+    /// or the default value type of the property
+    pub fn values(&self) -> Vec<Value> {
+        let value_type = self
+            .param("VALUE")
+            .map(|x| x.to_lowercase())
+            .unwrap_or_else(|| default_value_type(&self.name).to_owned());
+
+        let parts = |separator| -> Vec<&str> { self.value.split(separator).collect() };
+
+        match value_type.as_str() {
+            "date" => parts(',')
+                .into_iter()
+                .map(|x| Value::Date(format_date(x)))
+                .collect(),
+            "date-time" => parts(',')
+                .into_iter()
+                .map(|x| Value::DateTime(format_date_time(x)))
+                .collect(),
+            "utc-offset" => vec![Value::UtcOffset(format_utc_offset(&self.value))],
+            "duration" => vec![Value::Duration(self.value.clone())],
+            "uri" => vec![Value::Uri(self.value.clone())],
+            "cal-address" => vec![Value::CalAddress(self.value.clone())],
+            "integer" => self
+                .value
+                .parse()
+                .map(|x| vec![Value::Integer(x)])
+                .unwrap_or_else(|_| vec![Value::Unknown(self.value.clone())]),
+            "float" => parts(';')
+                .into_iter()
+                .map(|x| {
+                    x.parse()
+                        .map(Value::Float)
+                        .unwrap_or(Value::Unknown(x.to_owned()))
+                })
+                .collect(),
+            "recur" => vec![Value::Recur(parse_recur(&self.value))],
+            "text" if self.name.eq_ignore_ascii_case("CATEGORIES") => split_text(&self.value)
+                .into_iter()
+                .map(Value::Text)
+                .collect(),
+            "text" => vec![Value::Text(self.text())],
+            _ => vec![Value::Unknown(self.value.clone())],
+        }
+    }
+}
+
+fn default_value_type(name: &str) -> &'static str {
+    match name.to_uppercase().as_str() {
+        "DTSTART" | "DTEND" | "DUE" | "DTSTAMP" | "CREATED" | "LAST-MODIFIED" | "COMPLETED"
+        | "EXDATE" | "RDATE" | "RECURRENCE-ID" => "date-time",
+        "TZOFFSETFROM" | "TZOFFSETTO" => "utc-offset",
+        "DURATION" | "TRIGGER" | "REFRESH-INTERVAL" => "duration",
+        "URL" | "SOURCE" | "TZURL" | "ATTACH" => "uri",
+        "ORGANIZER" | "ATTENDEE" => "cal-address",
+        "SEQUENCE" | "PRIORITY" | "REPEAT" | "PERCENT-COMPLETE" => "integer",
+        "GEO" => "float",
+        "RRULE" | "EXRULE" => "recur",
+        name if name.starts_with("X-") => "unknown",
+        _ => "text",
+    }
+}
+
+/// 20240105 -> 2024-01-05
+fn format_date(value: &str) -> String {
+    if value.len() == 8 && value.bytes().all(|b| b.is_ascii_digit()) {
+        format!("{}-{}-{}", &value[..4], &value[4..6], &value[6..])
+    } else {
+        value.to_owned()
+    }
+}
+
+/// 20240105T083000Z -> 2024-01-05T08:30:00Z
+fn format_date_time(value: &str) -> String {
+    match value.split_once('T') {
+        Some((date, time)) if time.len() >= 6 => format!(
+            "{}T{}:{}:{}{}",
+            format_date(date),
+            &time[..2],
+            &time[2..4],
+            &time[4..6],
+            &time[6..]
+        ),
+        _ => format_date(value),
+    }
+}
+
+/// +0600 -> +06:00
+fn format_utc_offset(value: &str) -> String {
+    if value.len() >= 5 {
+        format!("{}:{}", &value[..3], &value[3..])
+    } else {
+        value.to_owned()
+    }
+}
+
+fn parse_recur(value: &str) -> Vec<(String, Vec<String>)> {
+    value
+        .split(';')
+        .filter_map(|part| part.split_once('='))
+        .map(|(key, values)| {
+            let key = key.to_lowercase();
+            let values = values
+                .split(',')
+                .map(|x| match key.as_str() {
+                    "until" => format_date_time(x),
+                    _ => x.to_owned(),
+                })
+                .collect();
+            (key, values)
+        })
+        .collect()
+}
+
+/// Reverses the escaping of RFC 5545 section 3.3.11
+pub fn unescape_text(value: &str) -> String {
+    let mut output = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') | Some('N') => output.push('\n'),
+                Some(other) => output.push(other),
+                None => output.push('\\'),
+            }
+        } else {
+            output.push(c);
+        }
+    }
+    output
+}
+
+/// Splits a multi-valued text property on unescaped commas
+pub fn split_text(value: &str) -> Vec<String> {
+    let mut parts = vec![];
+    let mut start = 0;
+    let mut escaped = false;
+    for (i, c) in value.char_indices() {
+        match c {
+            '\\' if !escaped => escaped = true,
+            ',' if !escaped => {
+                parts.push(unescape_text(&value[start..i]));
+                start = i + 1;
+            }
+            _ => escaped = false,
+        }
+    }
+    parts.push(unescape_text(&value[start..]));
+    parts
+}
+
+/// Joins folded content lines back together
+pub fn unfold(input: &str) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+    for line in input.split('\n') {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        match line.strip_prefix([' ', '\t']) {
+            Some(continuation) if !lines.is_empty() => {
+                lines.last_mut().unwrap().push_str(continuation)
+            }
+            _ if line.is_empty() => {}
+            _ => lines.push(line.to_owned()),
+        }
+    }
+    lines
+}
+
+/// Folds a content line so no physical line, including the leading
+/// space of continuations, is over 75 octets. Characters are never split
+pub fn fold(line: &str) -> String {
+    const MAX_OCTETS: usize = 75;
+
+    let mut folded = String::with_capacity(line.len() + line.len() / MAX_OCTETS * 3);
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > MAX_OCTETS {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+        folded.push(c);
+        length += c.len_utf8();
+    }
+    folded
+}
+
+fn parse_content_line(line: &str) -> Result<Property, Box<dyn Error>> {
+    // the value starts at the first colon outside of a quoted parameter value
+    let mut in_quotes = false;
+    let colon = line
+        .char_indices()
+        .find(|(_, c)| {
+            if *c == '"' {
+                in_quotes = !in_quotes;
+            }
+            *c == ':' && !in_quotes
+        })
+        .map(|(i, _)| i)
+        .ok_or_else(|| format!("Invalid content line: {line}"))?;
+
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+    let mut head = head.split(';');
+    let name = head.next().unwrap_or_default().to_uppercase();
+
+    if name.is_empty() {
+        return Err(format!("Invalid content line: {line}").into());
+    }
+
+    let params = head
+        .filter_map(|param| param.split_once('='))
+        .map(|(key, value)| (key.to_uppercase(), value.trim_matches('"').to_owned()))
+        .collect();
+
+    Ok(Property {
+        name,
+        params,
+        value: value.to_owned(),
+    })
+}
+
+/// Parses iCalendar text into its top level component, usually VCALENDAR
+pub fn parse(input: &str) -> Result<Component, Box<dyn Error>> {
+    let mut stack: Vec<Component> = vec![];
+    let mut root = None;
+
+    for line in unfold(input) {
+        let property = parse_content_line(&line)?;
+        match property.name.as_str() {
+            "BEGIN" => stack.push(Component {
+                name: property.value.to_uppercase(),
+                properties: vec![],
+                components: vec![],
+            }),
+            "END" => {
+                let component = stack.pop().ok_or("END without BEGIN")?;
+                if !component.name.eq_ignore_ascii_case(&property.value) {
+                    return Err(format!("Mismatched END:{}", property.value).into());
+                }
+                match stack.last_mut() {
+                    Some(parent) => parent.components.push(component),
+                    None => root = Some(component),
+                }
+            }
+            _ => stack
+                .last_mut()
+                .ok_or("Property outside of a component")?
+                .properties
+                .push(property),
+        }
+    }
+
+    if !stack.is_empty() {
+        return Err("Unterminated component".into());
+    }
+
+    root.ok_or_else(|| "No calendar found".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let input = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nBEGIN:VEVENT\r\nDTSTART;VALUE=DATE:20240105\r\nSUMMARY;LANGUAGE=en:Classes\\, labs and\r\n  tutorials begin\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+
+        let calendar = parse(input).unwrap();
+        assert_eq!(calendar.name, "VCALENDAR");
+
+        let event = calendar.components_named("VEVENT").next().unwrap();
+        let summary = event.property("SUMMARY").unwrap();
+        assert_eq!(summary.param("language"), Some("en"));
+        assert_eq!(summary.text(), "Classes, labs and tutorials begin");
+        assert_eq!(
+            event.property("DTSTART").unwrap().values(),
+            vec![Value::Date("2024-01-05".to_owned())]
+        );
+
+        assert!(parse("BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nEND:VCALENDAR\r\n").is_err());
+    }
+
+    #[test]
+    fn test_fold() {
+        let line = format!("SUMMARY:{}", "ঈদুল ফিতর ".repeat(12));
+
+        let folded = fold(&line);
+
+        let physical = folded.split("\r\n").collect::<Vec<_>>();
+        assert!(physical.len() > 2);
+        assert!(physical.iter().all(|x| x.len() <= 75), "{physical:#?}");
+        assert!(physical[1..].iter().all(|x| x.starts_with(' ')));
+        assert_eq!(unfold(&(folded + "\r\n")), vec![line]);
+
+        assert_eq!(fold("SUMMARY:Classes begin"), "SUMMARY:Classes begin");
+        assert_eq!(fold(&"a".repeat(76)), format!("{}\r\n a", "a".repeat(75)));
+    }
+}
@@ -0,0 +1,164 @@
+//! Reads iCalendar files, such as ones saved from earlier semesters, back into `CalendarDetails`
+
+use chrono::{Datelike, Days, NaiveDate};
+use regex::Regex;
+use std::error::Error;
+
+use crate::ical::{self, Component, Property};
+use crate::parser::{CalendarDetails, Entry, DATE_TEXT_LABEL};
+
+/// PRODID of calendars generated by early versions of this project,
+/// whose DTEND is the last day of an event rather than the day after
+const LEGACY_PRODID: &str = "icalendar";
+
+fn parse_date(property: &Property) -> Result<NaiveDate, Box<dyn Error>> {
+    let value = property.value.get(..8).unwrap_or_default();
+    NaiveDate::parse_from_str(value, "%Y%m%d")
+        .map_err(|_| format!("Invalid {}: {}", property.name, property.value).into())
+}
+
+fn event_dates(
+    event: &Component,
+    inclusive_end: bool,
+) -> Result<(NaiveDate, Option<NaiveDate>), Box<dyn Error>> {
+    let start = parse_date(event.property("DTSTART").ok_or("Event without DTSTART")?)?;
+
+    let end = match event.property("DTEND") {
+        Some(dtend) => {
+            let end = parse_date(dtend)?;
+            let is_date = dtend
+                .param("VALUE")
+                .is_some_and(|x| x.eq_ignore_ascii_case("DATE"));
+            if is_date && !inclusive_end {
+                end.checked_sub_days(Days::new(1)).unwrap_or(end)
+            } else {
+                end
+            }
+        }
+        None => start,
+    };
+
+    Ok((start, (end > start).then_some(end)))
+}
+
+/// Parses iCalendar text into calendar details.
+/// Semester and year are read from the calendar name when it starts with them, eg: Spring 2024 Graduate
+pub fn parse_ics(ics: &str) -> Result<CalendarDetails, Box<dyn Error>> {
+    let calendar = ical::parse(ics)?;
+    if calendar.name != "VCALENDAR" {
+        return Err("Not an iCalendar file".into());
+    }
+
+    let events = calendar.components_named("VEVENT").collect::<Vec<_>>();
+
+    let legacy = calendar
+        .property("PRODID")
+        .is_some_and(|x| x.value == LEGACY_PRODID);
+    // legacy calendars gave single day events a DTEND equal to DTSTART
+    let inclusive_end = legacy
+        && events.iter().any(|event| {
+            let date = |name| event.property(name).map(|x| x.value.as_str());
+            date("DTEND").is_some() && date("DTEND") == date("DTSTART")
+        });
+
+    let mut entries = vec![];
+    for event in &events {
+        // calendars generated here keep the date as written on the calendar in the description
+        let date_text = event
+            .property("DESCRIPTION")
+            .map(Property::text)
+            .and_then(|description| {
+                description
+                    .lines()
+                    .find_map(|line| line.strip_prefix(DATE_TEXT_LABEL))
+                    .map(str::to_owned)
+            })
+            .unwrap_or_default();
+
+        entries.push(Entry {
+            date: event_dates(event, inclusive_end)?,
+            date_text,
+            event: event
+                .property("SUMMARY")
+                .map(Property::text)
+                .unwrap_or_default(),
+        });
+    }
+
+    let revised_date = events
+        .iter()
+        .filter_map(|event| {
+            event
+                .property("LAST-MODIFIED")
+                .or(event.property("DTSTAMP"))
+        })
+        .filter_map(|x| parse_date(x).ok())
+        .max()
+        .or_else(|| entries.iter().map(|x| x.start()).min())
+        .ok_or("Calendar has no events")?;
+
+    let name = calendar
+        .property("X-WR-CALNAME")
+        .or(calendar.property("NAME"))
+        .map(Property::text)
+        .unwrap_or_default();
+    let name_regex = Regex::new(r"(?i)^(spring|summer|fall)\s(\d{4})\s*(.*)$").unwrap();
+
+    let (semester, year, calendar_name) = match name_regex.captures(&name) {
+        Some(captures) => (
+            captures[1].to_string(),
+            captures[2].parse()?,
+            captures[3].to_string(),
+        ),
+        None => (String::new(), revised_date.year(), name),
+    };
+
+    Ok(CalendarDetails {
+        calendar_name,
+        semester,
+        revised_date,
+        entries,
+        year,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::generate_ics;
+    use crate::test_util::{calendar, date, entry};
+
+    #[test]
+    fn test_round_trip() {
+        let calendar = calendar(vec![
+            Entry {
+                date_text: "7 January".to_string(),
+                ..entry(date(1, 7), None, "Classes begin")
+            },
+            entry(
+                date(4, 9),
+                Some(date(4, 13)),
+                "Eid-ul-Fitr, Pohela Boishakh",
+            ),
+        ]);
+
+        let imported = parse_ics(&generate_ics(calendar.clone())).unwrap();
+
+        assert_eq!(imported, calendar);
+    }
+
+    #[test]
+    fn test_exclusive_dtend() {
+        let ics = "BEGIN:VCALENDAR\r\nPRODID:-//Example//EN\r\nX-WR-CALNAME:Fall 2023 Graduate\r\nBEGIN:VEVENT\r\nDTSTART;VALUE=DATE:20231010\r\nDTEND;VALUE=DATE:20231013\r\nLAST-MODIFIED:20230901T000000\r\nSUMMARY:Mid-term Examination\r\nEND:VEVENT\r\nBEGIN:VEVENT\r\nDTSTART;VALUE=DATE:20231020\r\nDTEND;VALUE=DATE:20231021\r\nSUMMARY:Holiday\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+
+        let imported = parse_ics(ics).unwrap();
+        let date = |month, day| NaiveDate::from_ymd_opt(2023, month, day).unwrap();
+
+        assert_eq!(imported.semester, "Fall");
+        assert_eq!(imported.year, 2023);
+        assert_eq!(imported.calendar_name, "Graduate");
+        assert_eq!(imported.revised_date, date(9, 1));
+        assert_eq!(imported.entries[0].date, (date(10, 10), Some(date(10, 12))));
+        assert_eq!(imported.entries[1].date, (date(10, 20), None));
+    }
+}
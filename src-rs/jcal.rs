@@ -0,0 +1,108 @@
+//! jCal (RFC 7265), the JSON format for iCalendar
+
+use serde_json::{json, Map, Value as Json};
+use std::error::Error;
+
+use crate::ical::{self, Component, Property, Value};
+
+fn value_to_json(value: &Value) -> Json {
+    match value {
+        Value::Integer(x) => json!(x),
+        Value::Float(x) => json!(x),
+        Value::Recur(parts) => {
+            let recur = parts
+                .iter()
+                .map(|(key, values)| match values.as_slice() {
+                    [single] => (key.clone(), json!(single)),
+                    _ => (key.clone(), json!(values)),
+                })
+                .collect::<Map<String, Json>>();
+            Json::Object(recur)
+        }
+        Value::Text(x)
+        | Value::Date(x)
+        | Value::DateTime(x)
+        | Value::UtcOffset(x)
+        | Value::Duration(x)
+        | Value::Uri(x)
+        | Value::CalAddress(x)
+        | Value::Unknown(x) => json!(x),
+    }
+}
+
+fn property_to_json(property: &Property) -> Json {
+    let params = property
+        .params
+        .iter()
+        .filter(|(key, _)| key != "VALUE")
+        .map(|(key, value)| (key.to_lowercase(), json!(value)))
+        .collect::<Map<String, Json>>();
+
+    let values = property.values();
+    let type_name = values.first().map_or("unknown", Value::type_name);
+
+    let mut jprop = vec![
+        json!(property.name.to_lowercase()),
+        Json::Object(params),
+        json!(type_name),
+    ];
+
+    // structured values like GEO are a single array of their parts
+    if property.name == "GEO" {
+        jprop.push(Json::Array(values.iter().map(value_to_json).collect()));
+    } else {
+        jprop.extend(values.iter().map(value_to_json));
+    }
+
+    Json::Array(jprop)
+}
+
+pub fn component_to_json(component: &Component) -> Json {
+    json!([
+        component.name.to_lowercase(),
+        component
+            .properties
+            .iter()
+            .map(property_to_json)
+            .collect::<Vec<_>>(),
+        component
+            .components
+            .iter()
+            .map(component_to_json)
+            .collect::<Vec<_>>(),
+    ])
+}
+
+/// Converts iCalendar text into jCal
+pub fn from_ics(ics: &str) -> Result<Json, Box<dyn Error>> {
+    Ok(component_to_json(&ical::parse(ics)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_ics() {
+        let ics = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nBEGIN:VEVENT\r\nDTSTART;VALUE=DATE:20240105\r\nDTSTAMP:20240101T000000\r\nGEO:23.7;90.4\r\nRRULE:FREQ=WEEKLY;BYDAY=SU,TU\r\nSUMMARY:Add\\, drop\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+
+        assert_eq!(
+            from_ics(ics).unwrap(),
+            json!([
+                "vcalendar",
+                [["version", {}, "text", "2.0"]],
+                [[
+                    "vevent",
+                    [
+                        ["dtstart", {}, "date", "2024-01-05"],
+                        ["dtstamp", {}, "date-time", "2024-01-01T00:00:00"],
+                        ["geo", {}, "float", [23.7, 90.4]],
+                        ["rrule", {}, "recur", { "freq": "WEEKLY", "byday": ["SU", "TU"] }],
+                        ["summary", {}, "text", "Add, drop"]
+                    ],
+                    []
+                ]]
+            ])
+        );
+    }
+}
@@ -1,3 +1,29 @@
+pub mod cache;
+pub mod calendar_path;
+pub mod category;
+pub mod conformance;
+pub mod csv;
+pub mod date_conversion;
 pub mod date_parser;
+pub mod diff;
+pub mod error;
+pub mod exam;
+pub mod format;
+pub mod holidays;
+pub mod ical;
+pub mod import;
+pub mod jcal;
+pub mod locale;
+pub mod options;
 pub mod parser;
+pub mod pdf;
+pub mod render;
+pub mod routine;
+pub mod scraper_profile;
+pub mod stats;
+#[cfg(test)]
+mod test_util;
 pub mod utils;
+pub mod v1;
+pub mod webhook;
+pub mod xcal;
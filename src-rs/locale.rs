@@ -0,0 +1,340 @@
+//! Bengali translations of calendar text, selected by the `lang` query parameter.
+//! Known phrases, such as event types and holiday names, are translated, as are names of
+//! months and weekdays in dates, and digits are written as Bengali numerals.
+//! Anything else is left in English
+
+use std::cmp::Reverse;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use crate::error::ApiError;
+use crate::parser::CalendarDetails;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Lang {
+    #[default]
+    En,
+    Bn,
+    /// Bengali followed by the English original
+    Bilingual,
+}
+
+/// Lowercase English phrases and their Bengali translations
+const PHRASES: &[(&str, &str)] = &[
+    // event types
+    ("mid-term examination", "মধ্যবর্তী পরীক্ষা"),
+    ("midterm examination", "মধ্যবর্তী পরীক্ষা"),
+    ("final examinations", "চূড়ান্ত পরীক্ষা"),
+    ("final examination", "চূড়ান্ত পরীক্ষা"),
+    ("final exam", "চূড়ান্ত পরীক্ষা"),
+    ("examinations", "পরীক্ষা"),
+    ("examination", "পরীক্ষা"),
+    ("exam", "পরীক্ষা"),
+    ("first day of classes", "ক্লাস শুরু"),
+    ("classes begin", "ক্লাস শুরু"),
+    ("last day of classes", "ক্লাসের শেষ দিন"),
+    ("classes", "ক্লাস"),
+    ("class routine", "ক্লাস রুটিন"),
+    (
+        "last date of tuition fee payment",
+        "টিউশন ফি পরিশোধের শেষ তারিখ",
+    ),
+    ("last date of payment", "পরিশোধের শেষ তারিখ"),
+    ("last date of course withdrawal", "কোর্স প্রত্যাহারের শেষ তারিখ"),
+    ("last date", "শেষ তারিখ"),
+    ("last day", "শেষ দিন"),
+    ("tuition fees", "টিউশন ফি"),
+    ("tuition fee", "টিউশন ফি"),
+    ("payment", "পরিশোধ"),
+    ("withdrawal", "প্রত্যাহার"),
+    ("online advising", "অনলাইন অ্যাডভাইজিং"),
+    ("advising", "অ্যাডভাইজিং"),
+    ("registration", "নিবন্ধন"),
+    ("add/drop", "কোর্স যোগ/বাদ"),
+    ("orientation", "ওরিয়েন্টেশন"),
+    ("submission of grades", "গ্রেড জমা"),
+    ("grade submission", "গ্রেড জমা"),
+    ("publication of results", "ফলাফল প্রকাশ"),
+    ("result publication", "ফলাফল প্রকাশ"),
+    ("convocation", "সমাবর্তন"),
+    ("semester break", "সেমিস্টার বিরতি"),
+    ("semester", "সেমিস্টার"),
+    ("university will remain closed", "বিশ্ববিদ্যালয় বন্ধ থাকবে"),
+    ("university closed", "বিশ্ববিদ্যালয় বন্ধ"),
+    ("public holiday", "সরকারি ছুটি"),
+    ("holidays", "ছুটি"),
+    ("holiday", "ছুটি"),
+    ("vacation", "ছুটি"),
+    // holidays
+    ("eid-ul-fitr", "ঈদুল ফিতর"),
+    ("eid-ul-adha", "ঈদুল আযহা"),
+    ("eid-ul-azha", "ঈদুল আযহা"),
+    ("eid-e-miladunnabi", "ঈদে মিলাদুন্নবী"),
+    ("shab-e-barat", "শবে বরাত"),
+    ("shab-e-qadr", "শবে কদর"),
+    ("jumatul bidah", "জুমাতুল বিদা"),
+    ("ashura", "আশুরা"),
+    ("pohela boishakh", "পহেলা বৈশাখ"),
+    ("bengali new year", "বাংলা নববর্ষ"),
+    ("international mother language day", "আন্তর্জাতিক মাতৃভাষা দিবস"),
+    ("shaheed dibosh", "শহীদ দিবস"),
+    ("independence day", "স্বাধীনতা দিবস"),
+    ("victory day", "বিজয় দিবস"),
+    ("national mourning day", "জাতীয় শোক দিবস"),
+    ("may day", "মে দিবস"),
+    ("durga puja", "দুর্গাপূজা"),
+    ("janmashtami", "জন্মাষ্টমী"),
+    ("buddha purnima", "বুদ্ধ পূর্ণিমা"),
+    ("christmas day", "বড়দিন"),
+    ("christmas", "বড়দিন"),
+    // calendar names
+    (
+        "east west university academic calendar of",
+        "ইস্ট ওয়েস্ট ইউনিভার্সিটির একাডেমিক ক্যালেন্ডার:",
+    ),
+    ("east west university", "ইস্ট ওয়েস্ট ইউনিভার্সিটি"),
+    ("revised", "সংশোধিত"),
+    ("undergraduate", "স্নাতক"),
+    ("graduate", "স্নাতকোত্তর"),
+];
+
+/// Names of seasons, months and weekdays, which are only translated in dates and semester
+/// names: elsewhere they can be ordinary words, eg: "may", "sun" or "fall"
+const DATE_WORDS: &[(&str, &str)] = &[
+    // seasons, which name the semesters
+    ("spring", "বসন্ত"),
+    ("summer", "গ্রীষ্ম"),
+    ("fall", "শরৎ"),
+    // months
+    ("january", "জানুয়ারি"),
+    ("february", "ফেব্রুয়ারি"),
+    ("march", "মার্চ"),
+    ("april", "এপ্রিল"),
+    ("may", "মে"),
+    ("june", "জুন"),
+    ("july", "জুলাই"),
+    ("august", "আগস্ট"),
+    ("september", "সেপ্টেম্বর"),
+    ("october", "অক্টোবর"),
+    ("november", "নভেম্বর"),
+    ("december", "ডিসেম্বর"),
+    // months of the Bangla and Hijri calendars
+    ("bangabda", "বঙ্গাব্দ"),
+    ("boishakh", "বৈশাখ"),
+    ("joishtho", "জ্যৈষ্ঠ"),
+    ("asharh", "আষাঢ়"),
+    ("shrabon", "শ্রাবণ"),
+    ("bhadro", "ভাদ্র"),
+    ("ashwin", "আশ্বিন"),
+    ("kartik", "কার্তিক"),
+    ("ogrohayon", "অগ্রহায়ণ"),
+    ("poush", "পৌষ"),
+    ("magh", "মাঘ"),
+    ("falgun", "ফাল্গুন"),
+    ("choitro", "চৈত্র"),
+    ("hijri", "হিজরি"),
+    ("muharram", "মহররম"),
+    ("safar", "সফর"),
+    ("rabi al-awwal", "রবিউল আউয়াল"),
+    ("rabi al-thani", "রবিউস সানি"),
+    ("jumada al-ula", "জমাদিউল আউয়াল"),
+    ("jumada al-akhirah", "জমাদিউস সানি"),
+    ("rajab", "রজব"),
+    ("shaban", "শাবান"),
+    ("ramadan", "রমজান"),
+    ("shawwal", "শাওয়াল"),
+    ("dhu al-qadah", "জিলকদ"),
+    ("dhu al-hijjah", "জিলহজ"),
+    // weekdays
+    ("sunday", "রবিবার"),
+    ("monday", "সোমবার"),
+    ("tuesday", "মঙ্গলবার"),
+    ("wednesday", "বুধবার"),
+    ("thursday", "বৃহস্পতিবার"),
+    ("friday", "শুক্রবার"),
+    ("saturday", "শনিবার"),
+    ("sun", "রবি"),
+    ("mon", "সোম"),
+    ("tue", "মঙ্গল"),
+    ("wed", "বুধ"),
+    ("thu", "বৃহস্পতি"),
+    ("fri", "শুক্র"),
+    ("sat", "শনি"),
+];
+
+/// Phrases of the tables ordered longest first, so the most specific phrase wins
+fn longest_first(tables: &[&[(&'static str, &'static str)]]) -> Vec<(&'static str, &'static str)> {
+    let mut phrases = tables.concat();
+    phrases.sort_by_key(|(english, _)| Reverse(english.len()));
+    phrases
+}
+
+fn phrases() -> &'static [(&'static str, &'static str)] {
+    static SORTED: OnceLock<Vec<(&str, &str)>> = OnceLock::new();
+    SORTED.get_or_init(|| longest_first(&[PHRASES]))
+}
+
+fn date_phrases() -> &'static [(&'static str, &'static str)] {
+    static SORTED: OnceLock<Vec<(&str, &str)>> = OnceLock::new();
+    SORTED.get_or_init(|| longest_first(&[PHRASES, DATE_WORDS]))
+}
+
+/// Writes ASCII digits as Bengali numerals, eg: 2024 -> ২০২৪
+pub fn bengali_digits(text: &str) -> String {
+    text.chars()
+        .map(|c| match c.to_digit(10) {
+            Some(digit) if c.is_ascii_digit() => char::from_u32('০' as u32 + digit).unwrap_or(c),
+            _ => c,
+        })
+        .collect()
+}
+
+/// Replaces the phrases found in English text by their Bengali translations.
+/// Phrases only match whole words, ignoring ASCII case
+fn replace_phrases(english: &str, phrases: &[(&str, &str)]) -> String {
+    // ASCII lowercasing keeps byte offsets, so they're shared by both strings
+    let lower = english.to_ascii_lowercase();
+    let is_word = |c: char| c.is_alphanumeric();
+
+    let mut translated = String::with_capacity(english.len() * 2);
+    let mut i = 0;
+    'text: while i < english.len() {
+        let word_start = !english[..i].chars().next_back().is_some_and(is_word);
+        if word_start {
+            for (phrase, bengali) in phrases {
+                let end = i + phrase.len();
+                if lower[i..].starts_with(phrase)
+                    && !english[end..].chars().next().is_some_and(is_word)
+                {
+                    translated.push_str(bengali);
+                    i = end;
+                    continue 'text;
+                }
+            }
+        }
+        let c = english[i..].chars().next().unwrap_or_default();
+        translated.push(c);
+        i += c.len_utf8();
+    }
+
+    bengali_digits(&translated)
+}
+
+/// Translates the known phrases of English text into Bengali
+pub fn translate(english: &str) -> String {
+    replace_phrases(english, phrases())
+}
+
+/// Translates a date or semester name, eg: 9 - 13 April, including names of months and weekdays
+pub fn translate_date(english: &str) -> String {
+    replace_phrases(english, date_phrases())
+}
+
+impl Lang {
+    /// Value of the LANGUAGE parameter of text in this language.
+    /// Bilingual text leads with Bengali and only repeats the English original after it,
+    /// so it is tagged as Bengali too. English text is left untagged, as before translations
+    pub fn tag(&self) -> Option<&'static str> {
+        match self {
+            Lang::Bn | Lang::Bilingual => Some("bn"),
+            Lang::En => None,
+        }
+    }
+
+    /// English text in this language
+    pub fn text(&self, english: &str) -> String {
+        self.translated(english, translate)
+    }
+
+    /// A date or semester name in this language
+    pub fn date_text(&self, english: &str) -> String {
+        self.translated(english, translate_date)
+    }
+
+    fn translated(&self, english: &str, translate: fn(&str) -> String) -> String {
+        match self {
+            Lang::En => english.to_owned(),
+            Lang::Bn => translate(english),
+            Lang::Bilingual => {
+                let bengali = translate(english);
+                if bengali == english {
+                    bengali
+                } else {
+                    format!("{bengali} ({english})")
+                }
+            }
+        }
+    }
+}
+
+/// Translates the text of a calendar, leaving dates and the year as they are
+pub fn localize(calendar: &CalendarDetails, lang: Lang) -> CalendarDetails {
+    let mut localized = calendar.clone();
+    localized.calendar_name = lang.text(&calendar.calendar_name);
+    localized.semester = lang.date_text(&calendar.semester);
+    for entry in &mut localized.entries {
+        entry.event = lang.text(&entry.event);
+        entry.date_text = lang.date_text(&entry.date_text);
+    }
+    localized
+}
+
+impl FromStr for Lang {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "en" | "english" => Ok(Lang::En),
+            "bn" | "bangla" | "bengali" => Ok(Lang::Bn),
+            "bn-en" | "en-bn" | "both" | "bilingual" => Ok(Lang::Bilingual),
+            _ => Err(ApiError::BadRequest(format!("Unknown language \"{s}\""))),
+        }
+    }
+}
+
+impl fmt::Display for Lang {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Lang::En => "en",
+            Lang::Bn => "bn",
+            Lang::Bilingual => "bn-en",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_translate() {
+        assert_eq!(
+            translate("Mid-term Examination (Sec 1-5)"),
+            "মধ্যবর্তী পরীক্ষা (Sec ১-৫)"
+        );
+        assert_eq!(
+            translate("Eid-ul-Fitr and Pohela Boishakh"),
+            "ঈদুল ফিতর and পহেলা বৈশাখ"
+        );
+        assert_eq!(translate("May Day"), "মে দিবস");
+        // names of months and weekdays are only words of dates
+        assert_eq!(
+            translate("Class tests may fall on Sat"),
+            "Class tests may fall on Sat"
+        );
+        assert_eq!(translate_date("9 - 13 April"), "৯ - ১৩ এপ্রিল");
+        assert_eq!(translate_date("1 May (Wed)"), "১ মে (বুধ)");
+        // whole words only: "Fall" but not "Falls", "mon" but not "month"
+        assert_eq!(translate_date("Fall 2024"), "শরৎ ২০২৪");
+        assert_eq!(translate_date("Falls month"), "Falls month");
+
+        assert_eq!(Lang::En.text("Classes begin"), "Classes begin");
+        assert_eq!(
+            Lang::Bilingual.text("Classes begin"),
+            "ক্লাস শুরু (Classes begin)"
+        );
+        assert_eq!(Lang::Bilingual.text("Quiz"), "Quiz");
+        assert_eq!(Lang::Bilingual.tag(), Some("bn"));
+    }
+}
@@ -0,0 +1,406 @@
+//! Options for the events of generated iCalendar files,
+//! read from query parameters of the generate endpoint
+
+use reqwest::header::HOST;
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use vercel_runtime::Request;
+
+use crate::category::Category;
+use crate::error::ApiError;
+use crate::locale::Lang;
+use crate::utils;
+
+pub const DEFAULT_LOCATION: &str = "East West University, Dhaka";
+/// CSS color name clients show the calendar in
+pub const DEFAULT_COLOR: &str = "navy";
+
+/// How long before the start of an event an alarm goes off, eg: 1d, 2h, 30m
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Reminder {
+    pub minutes: u32,
+}
+
+impl Reminder {
+    pub const fn days(days: u32) -> Self {
+        Reminder {
+            minutes: days * 24 * 60,
+        }
+    }
+
+    /// Negative duration for the alarm's TRIGGER, eg: -P1DT12H
+    pub fn trigger(&self) -> String {
+        let (days, hours, minutes) = (
+            self.minutes / (24 * 60),
+            self.minutes / 60 % 24,
+            self.minutes % 60,
+        );
+
+        let mut trigger = String::from("-P");
+        if days > 0 {
+            trigger += &format!("{days}D");
+        }
+        if hours > 0 || minutes > 0 || days == 0 {
+            trigger.push('T');
+            if hours > 0 {
+                trigger += &format!("{hours}H");
+            }
+            if minutes > 0 || hours == 0 {
+                trigger += &format!("{minutes}M");
+            }
+        }
+        trigger
+    }
+}
+
+impl FromStr for Reminder {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            ApiError::BadRequest(format!(
+                "Invalid alarm \"{s}\", expected a number followed by w, d, h or m"
+            ))
+        };
+
+        let s = s.trim();
+        let unit = s.chars().last().ok_or_else(invalid)?;
+        let amount = s[..s.len() - unit.len_utf8()]
+            .parse::<u32>()
+            .map_err(|_| invalid())?;
+        let minutes = match unit.to_ascii_lowercase() {
+            'w' => amount.checked_mul(7 * 24 * 60),
+            'd' => amount.checked_mul(24 * 60),
+            'h' => amount.checked_mul(60),
+            'm' => Some(amount),
+            _ => None,
+        };
+
+        Ok(Reminder {
+            minutes: minutes.ok_or_else(invalid)?,
+        })
+    }
+}
+
+/// Exams block time in clients, everything else is informational
+const DEFAULT_BUSY: [Category; 1] = [Category::Exam];
+
+/// Deadlines remind a day ahead unless alarms are requested
+const DEFAULT_ALARMS: [(Category, Reminder); 1] = [(Category::Deadline, Reminder::days(1))];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IcsOptions {
+    /// Location of every event. Empty leaves the location out
+    pub location: String,
+    /// Locations overriding `location` for events of a category
+    pub category_locations: HashMap<Category, String>,
+    /// Calendar page on ewubd.edu the events link to
+    pub source_url: Option<String>,
+    /// Subscription url of the calendar itself
+    pub feed_url: Option<String>,
+    /// CSS color name of the calendar. Empty leaves the color out
+    pub color: String,
+    /// Latitude and longitude of the location
+    pub geo: Option<(f64, f64)>,
+    /// Email address of the organizer
+    pub organizer: Option<String>,
+    /// Alarms of every event
+    pub alarms: Vec<Reminder>,
+    /// Alarms replacing `alarms` for events of a category
+    pub category_alarms: HashMap<Category, Vec<Reminder>>,
+    /// Categories whose events show as busy. Others are transparent
+    pub busy: HashSet<Category>,
+    /// Language of titles and descriptions
+    pub lang: Lang,
+    /// Adds the dates in the Bangla and Hijri calendars to descriptions
+    pub annotate_dates: bool,
+}
+
+impl Default for IcsOptions {
+    fn default() -> Self {
+        IcsOptions {
+            location: DEFAULT_LOCATION.to_string(),
+            category_locations: HashMap::new(),
+            source_url: None,
+            feed_url: None,
+            color: DEFAULT_COLOR.to_string(),
+            geo: None,
+            organizer: None,
+            alarms: vec![],
+            category_alarms: DEFAULT_ALARMS
+                .iter()
+                .map(|(category, reminder)| (*category, vec![*reminder]))
+                .collect(),
+            busy: HashSet::from(DEFAULT_BUSY),
+            lang: Lang::default(),
+            annotate_dates: false,
+        }
+    }
+}
+
+impl IcsOptions {
+    /// Reads `location`, `location_<category>`, eg: `location_exam`, `geo`, `organizer`,
+    /// `alarm`, `alarm_<category>`, `color`, `busy`, `lang` and `annotate_dates`.
+    /// Alarms are comma separated, eg: `alarm=1d,2h`, and `alarm` replaces the default alarms.
+    /// `busy` lists the categories shown as busy, eg: `busy=exam,registration`, or is `all` or `none`.
+    /// The source url is the requested calendar's page
+    /// and the feed url its `/cal/<slug>.ics` route on the host serving the request
+    pub fn from_request(req: &Request) -> Result<Self, ApiError> {
+        let path = utils::get_calendar_path(req).ok();
+        let host = req.headers().get(HOST).and_then(|x| x.to_str().ok());
+        let mut options = IcsOptions {
+            source_url: path.as_ref().map(|path| path.url()),
+            feed_url: path
+                .zip(host)
+                .map(|(path, host)| format!("https://{host}/cal/{}.ics", path.slug())),
+            ..Default::default()
+        };
+
+        if let Some(location) = utils::get_query_param(req, "location")? {
+            options.location = location;
+        }
+        for category in Category::ALL {
+            let name = format!("location_{}", category.as_str());
+            if let Some(location) = utils::get_query_param(req, &name)? {
+                options.category_locations.insert(category, location);
+            }
+        }
+        options.geo = utils::get_query_param(req, "geo")?
+            .map(|geo| parse_geo(&geo))
+            .transpose()?;
+        options.organizer = utils::get_query_param(req, "organizer")?
+            .map(|organizer| parse_organizer(&organizer))
+            .transpose()?;
+        if let Some(color) = utils::get_query_param(req, "color")? {
+            if !color.chars().all(|c| c.is_ascii_alphabetic()) {
+                return Err(ApiError::BadRequest(format!(
+                    "Invalid color \"{color}\", expected a CSS color name"
+                )));
+            }
+            options.color = color.to_lowercase();
+        }
+
+        if let Some(alarms) = utils::get_query_param(req, "alarm")? {
+            options.alarms = parse_reminders(&alarms)?;
+            options.category_alarms.clear();
+        }
+        if let Some(lang) = utils::parse_query_param(req, "lang")? {
+            options.lang = lang;
+        }
+        if let Some(annotate_dates) = utils::get_query_param(req, "annotate_dates")? {
+            options.annotate_dates = match annotate_dates.to_lowercase().as_str() {
+                "1" | "true" | "yes" => true,
+                "0" | "false" | "no" => false,
+                _ => {
+                    return Err(ApiError::BadRequest(format!(
+                        "Invalid annotate_dates \"{annotate_dates}\", expected true or false"
+                    )))
+                }
+            };
+        }
+        if let Some(busy) = utils::get_query_param(req, "busy")? {
+            options.busy = match busy.trim().to_lowercase().as_str() {
+                "all" => HashSet::from(Category::ALL),
+                "none" | "" => HashSet::new(),
+                _ => busy.split(',').map(str::parse).collect::<Result<_, _>>()?,
+            };
+        }
+        for category in Category::ALL {
+            let name = format!("alarm_{}", category.as_str());
+            if let Some(alarms) = utils::get_query_param(req, &name)? {
+                options
+                    .category_alarms
+                    .insert(category, parse_reminders(&alarms)?);
+            }
+        }
+
+        Ok(options)
+    }
+
+    /// Location of an event of the category, if any
+    pub fn location_for(&self, category: Category) -> Option<&str> {
+        let location = self
+            .category_locations
+            .get(&category)
+            .unwrap_or(&self.location);
+        (!location.is_empty()).then_some(location.as_str())
+    }
+
+    /// Alarms of an event of the category
+    pub fn alarms_for(&self, category: Category) -> &[Reminder] {
+        self.category_alarms.get(&category).unwrap_or(&self.alarms)
+    }
+}
+
+/// Parses a comma separated list of reminders. Empty or `none` means no alarms
+fn parse_reminders(reminders: &str) -> Result<Vec<Reminder>, ApiError> {
+    if reminders.trim().eq_ignore_ascii_case("none") {
+        return Ok(vec![]);
+    }
+    reminders
+        .split(',')
+        .filter(|x| !x.trim().is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+/// Checks an organizer's email address is a plain `local@domain` addr-spec.
+/// It ends up in an ORGANIZER property, so nothing that could end the value or the line,
+/// eg: `:`, `;` or a line break, gets through
+fn parse_organizer(organizer: &str) -> Result<String, ApiError> {
+    let organizer = organizer.trim();
+    let is_local = |c: char| c.is_ascii_alphanumeric() || "!#$%&'*+-/=?^_`{|}~.".contains(c);
+    let is_label = |label: &str| {
+        !label.is_empty() && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    };
+
+    let valid = organizer.split_once('@').is_some_and(|(local, domain)| {
+        !local.is_empty()
+            && local.chars().all(is_local)
+            && domain.contains('.')
+            && domain.split('.').all(is_label)
+    });
+    if valid {
+        Ok(organizer.to_owned())
+    } else {
+        Err(ApiError::BadRequest(
+            "Invalid organizer, expected an email address, eg: registrar@ewubd.edu".to_string(),
+        ))
+    }
+}
+
+/// Parses a `latitude,longitude` pair
+fn parse_geo(geo: &str) -> Result<(f64, f64), ApiError> {
+    let invalid = || {
+        ApiError::BadRequest(format!(
+            "Invalid geo \"{geo}\", expected latitude,longitude"
+        ))
+    };
+
+    let (latitude, longitude) = geo.split_once(',').ok_or_else(invalid)?;
+    let latitude = latitude.trim().parse::<f64>().map_err(|_| invalid())?;
+    let longitude = longitude.trim().parse::<f64>().map_err(|_| invalid())?;
+
+    if (-90.0..=90.0).contains(&latitude) && (-180.0..=180.0).contains(&longitude) {
+        Ok((latitude, longitude))
+    } else {
+        Err(invalid())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::generate_ics_with_options;
+    use crate::test_util::{date, entry};
+    use vercel_runtime::Body;
+
+    fn request(query: &str) -> Request {
+        let mut req = Request::new(Body::Empty);
+        *req.uri_mut() = format!("/api/generate?{query}").parse().unwrap();
+        req
+    }
+
+    #[test]
+    fn test_from_request() {
+        let mut req = request(
+            "slug=spring-2024-graduate&location_exam=Room%20221&location_holiday=&geo=23.7686,90.4255",
+        );
+        req.headers_mut()
+            .insert(HOST, "ewubd-ical-generator.vercel.app".parse().unwrap());
+        let options = IcsOptions::from_request(&req).unwrap();
+
+        assert_eq!(
+            options.source_url.as_deref(),
+            Some("https://www.ewubd.edu/academic-calendar-details/spring-2024-graduate")
+        );
+        assert_eq!(
+            options.feed_url.as_deref(),
+            Some("https://ewubd-ical-generator.vercel.app/cal/spring-2024-graduate.ics")
+        );
+        assert_eq!(options.location_for(Category::Exam), Some("Room 221"));
+        assert_eq!(options.location_for(Category::Holiday), None);
+        assert_eq!(
+            options.location_for(Category::Other),
+            Some(DEFAULT_LOCATION)
+        );
+        assert_eq!(options.geo, Some((23.7686, 90.4255)));
+
+        assert_eq!(options.alarms_for(Category::Deadline), &[Reminder::days(1)]);
+        assert!(options.alarms_for(Category::Exam).is_empty());
+
+        assert!(IcsOptions::from_request(&request("geo=91,90")).is_err());
+        assert!(IcsOptions::from_request(&request("geo=dhaka")).is_err());
+        assert!(IcsOptions::from_request(&request("color=%23ff0000")).is_err());
+    }
+
+    #[test]
+    fn test_organizer() {
+        let options =
+            IcsOptions::from_request(&request("organizer=registrar%40ewubd.edu")).unwrap();
+        assert_eq!(options.organizer.as_deref(), Some("registrar@ewubd.edu"));
+
+        let rejected = [
+            "registrar%40ewubd.edu%0D%0AATTENDEE:mailto:x%40evil.com",
+            "registrar%40ewubd.edu;RSVP=TRUE",
+            "mailto:registrar%40ewubd.edu",
+            "registrar",
+            "%40ewubd.edu",
+            "registrar%40ewubd",
+        ];
+        for organizer in rejected {
+            let req = request(&format!("organizer={organizer}"));
+            assert!(
+                IcsOptions::from_request(&req).is_err(),
+                "{organizer} was accepted"
+            );
+        }
+    }
+
+    #[test]
+    fn test_alarms() {
+        let options =
+            IcsOptions::from_request(&request("alarm=1d,2h&alarm_exam=1w&alarm_holiday=none"))
+                .unwrap();
+        let triggers = |category| {
+            options
+                .alarms_for(category)
+                .iter()
+                .map(Reminder::trigger)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(triggers(Category::Deadline), vec!["-P1D", "-PT2H"]);
+        assert_eq!(triggers(Category::Exam), vec!["-P7D"]);
+        assert!(triggers(Category::Holiday).is_empty());
+        assert_eq!(Reminder { minutes: 90 }.trigger(), "-PT1H30M");
+        assert_eq!(Reminder { minutes: 0 }.trigger(), "-PT0M");
+        assert!(IcsOptions::from_request(&request("alarm=2x")).is_err());
+    }
+
+    #[test]
+    fn test_busy() {
+        let busy = |query| IcsOptions::from_request(&request(query)).map(|x| x.busy);
+
+        assert_eq!(busy(""), Ok(HashSet::from([Category::Exam])));
+        assert_eq!(
+            busy("busy=exam,Registration"),
+            Ok(HashSet::from([Category::Exam, Category::Registration]))
+        );
+        assert_eq!(busy("busy=all"), Ok(HashSet::from(Category::ALL)));
+        assert_eq!(busy("busy=none"), Ok(HashSet::new()));
+        assert!(busy("busy=lunch").is_err());
+
+        let calendar = crate::test_util::calendar(vec![
+            entry(date(3, 10), None, "Mid-term Examination"),
+            entry(date(3, 26), None, "Independence Day (Holiday)"),
+        ]);
+        let ics = generate_ics_with_options(calendar, &IcsOptions::default());
+        let transp = ics
+            .lines()
+            .filter(|line| line.starts_with("TRANSP:"))
+            .collect::<Vec<_>>();
+        assert_eq!(transp, vec!["TRANSP:OPAQUE", "TRANSP:TRANSPARENT"]);
+        assert!(ics.contains("X-MICROSOFT-CDO-BUSYSTATUS:FREE"));
+    }
+}
@@ -1,17 +1,41 @@
-use chrono::prelude::*;
+use chrono::{prelude::*, Days};
 use ics::{
     components::{Parameter, Property},
-    properties::{CalScale, DtEnd, DtStart, LastModified, Location, Method, Name, Summary},
-    Event, ICalendar, Standard, TimeZone as ICSTimeZone,
+    escape_text,
+    properties::{
+        CalScale, Categories, Color, Description, DtEnd, DtStart, Geo, LastModified, Location,
+        Method, Name, Organizer, RefreshInterval, Source, Summary, Transp, Trigger, URL,
+    },
+    Alarm, Event, ICalendar, Standard, TimeZone as ICSTimeZone,
 };
-use regex::Regex;
-use scraper::{Html, Selector};
-use serde::Serialize;
+use scraper::Html;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::error::Error;
 
+use crate::category::Category;
+use crate::date_conversion::{format_span, to_bangabda, to_hijri, ConvertedDate};
 use crate::date_parser::parse_date_range;
-
-#[derive(Serialize, Debug)]
+use crate::holidays::{official_holidays, PublicHoliday};
+use crate::ical;
+use crate::locale::{localize, Lang};
+use crate::options::IcsOptions;
+use crate::scraper_profile::ScraperProfile;
+
+/// Prefix of the description line holding an event's date as written on the calendar
+pub const DATE_TEXT_LABEL: &str = "Date: ";
+
+const ORGANIZER_NAME: &str = "East West University";
+/// CATEGORIES of events falling on national public holidays
+const PUBLIC_HOLIDAY_CATEGORY: &str = "Public holiday";
+
+/// Identifies the generator of the calendar, per RFC 5545 section 3.7.3
+pub const PRODID: &str = "-//ewubd-ical-generator//EWU Academic Calendar//EN";
+const TIMEZONE: &str = "Asia/Dhaka";
+/// How often subscribed clients should check for a revised calendar
+const REFRESH_INTERVAL: &str = "P1D";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct CalendarDetails {
     pub calendar_name: String,
     pub semester: String,
@@ -20,12 +44,87 @@ pub struct CalendarDetails {
     pub year: i32,
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Entry {
     pub date: (NaiveDate, Option<NaiveDate>),
+    /// Date as written on the calendar page, eg: 9 - 13 April
+    pub date_text: String,
     pub event: String,
 }
 
+impl Entry {
+    pub fn start(&self) -> NaiveDate {
+        self.date.0
+    }
+
+    /// Last day of the event, inclusive
+    pub fn end(&self) -> NaiveDate {
+        self.date.1.unwrap_or(self.date.0)
+    }
+
+    /// Number of days the event spans
+    pub fn days(&self) -> i64 {
+        (self.end() - self.start()).num_days() + 1
+    }
+}
+
+/// An entry of the `/api/entries` response, with its dates in the Bangla and Hijri calendars
+#[derive(Serialize, Debug, PartialEq)]
+pub struct AnnotatedEntry {
+    pub date: (NaiveDate, Option<NaiveDate>),
+    pub date_text: String,
+    pub event: String,
+    pub bangabda: (ConvertedDate, Option<ConvertedDate>),
+    pub hijri: (ConvertedDate, Option<ConvertedDate>),
+    /// Names of the public holidays the entry falls on
+    pub official_holidays: Vec<String>,
+}
+
+/// The `/api/entries` response: a calendar in the requested language,
+/// with each entry annotated
+#[derive(Serialize, Debug, PartialEq)]
+pub struct AnnotatedCalendarDetails {
+    pub calendar_name: String,
+    pub semester: String,
+    pub revised_date: NaiveDate,
+    pub entries: Vec<AnnotatedEntry>,
+    pub year: i32,
+}
+
+impl AnnotatedCalendarDetails {
+    pub fn new(calendar: &CalendarDetails, lang: Lang) -> Self {
+        // public holidays are found from the English text, so translation comes last
+        let localized = localize(calendar, lang);
+        let entries = calendar
+            .entries
+            .iter()
+            .zip(localized.entries)
+            .map(|(entry, localized)| {
+                let (start, end) = entry.date;
+                AnnotatedEntry {
+                    official_holidays: official_holidays(entry)
+                        .into_iter()
+                        .map(|x| x.name.clone())
+                        .collect(),
+                    bangabda: (to_bangabda(start), end.map(to_bangabda)),
+                    hijri: (to_hijri(start), end.map(to_hijri)),
+                    date: entry.date,
+                    date_text: localized.date_text,
+                    event: localized.event,
+                }
+            })
+            .collect();
+
+        AnnotatedCalendarDetails {
+            calendar_name: localized.calendar_name,
+            semester: localized.semester,
+            revised_date: calendar.revised_date,
+            entries,
+            year: calendar.year,
+        }
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub enum Semester {
     Spring(i32),
@@ -43,6 +142,8 @@ pub struct Calendar {
 pub struct Program {
     pub program_type: String,
     pub calendars: Vec<Calendar>,
+    /// Midterm and final exam schedules, listed alongside the calendars
+    pub exam_schedules: Vec<Calendar>,
 }
 
 #[derive(Debug, Serialize)]
@@ -55,122 +156,133 @@ pub struct CalendarList {
 /// If event month is behind publish month,
 /// then assumes that event year is later than publish year,
 /// or else assumes that event year is the same as publish year
-fn with_event_year(event_date: NaiveDate, publish_date: NaiveDate) -> NaiveDate {
+fn with_event_year(event_date: NaiveDate, publish_date: NaiveDate) -> Option<NaiveDate> {
     let month_diff = event_date.month() as i32 - publish_date.month() as i32;
     let event_year = if month_diff.is_negative() {
         publish_date.year() + 1
     } else {
         publish_date.year()
     };
-    event_date.with_year(event_year).unwrap()
+    event_date.with_year(event_year)
 }
 
-pub fn collect_all_calendars(doc: &Html) -> Vec<CalendarList> {
-    let years = get_years(doc);
+pub fn collect_all_calendars(doc: &Html) -> Result<Vec<CalendarList>, String> {
+    let years = get_years(doc)?;
 
     years
         .iter()
         .map(|year| {
-            let programs = get_programs(doc, year);
-            CalendarList {
+            let programs = get_programs(doc, year)?;
+            Ok(CalendarList {
                 year: year.to_owned(),
                 programs,
-            }
+            })
         })
         .collect()
 }
 
-pub fn get_years(doc: &Html) -> Vec<String> {
-    let year_selector = Selector::parse(".training-program-tab li").unwrap();
+pub fn get_years(doc: &Html) -> Result<Vec<String>, String> {
+    let profile = &ScraperProfile::global()?.listing;
 
     let years = doc
-        .select(&year_selector)
+        .select(&profile.year)
         .map(|el| el.text().collect::<String>())
         .collect::<Vec<String>>();
 
-    years
+    Ok(years)
 }
 
-pub fn get_programs(doc: &Html, year: &str) -> Vec<Program> {
-    let tab_selector = Selector::parse(&format!(".tab-content > [id=\"{}\"]", year)).unwrap();
-    let panel_heading_selector = Selector::parse(".panel-heading").unwrap();
-    let panel_body_selector = Selector::parse(".panel-body").unwrap();
-    let calendar_semester_selector = Selector::parse("ul > li > a").unwrap();
+pub fn get_programs(doc: &Html, year: &str) -> Result<Vec<Program>, String> {
+    let profile = &ScraperProfile::global()?.listing;
+    let Some(tab_selector) = profile.year_tab.for_year(year) else {
+        return Ok(vec![]);
+    };
 
-    let year_tab = doc.select(&tab_selector).next().unwrap();
+    let Some(year_tab) = doc.select(&tab_selector).next() else {
+        return Ok(vec![]);
+    };
 
     let panels = year_tab
-        .select(&panel_heading_selector)
+        .select(&profile.program_name)
         .map(|el| el.text().collect::<String>().trim().to_owned());
 
-    let calendars_panel = year_tab.select(&panel_body_selector).map(|el| {
-        el.select(&calendar_semester_selector).map(|el| Calendar {
-            name: el.text().collect::<String>().trim().to_owned(),
-            url: el.value().attr("href").unwrap().to_owned(),
+    let calendars_panel = year_tab.select(&profile.program_body).map(|el| {
+        el.select(&profile.calendar_link).filter_map(|el| {
+            Some(Calendar {
+                name: el.text().collect::<String>().trim().to_owned(),
+                url: el.value().attr("href")?.to_owned(),
+            })
         })
     });
 
     let calendars: Vec<Program> = panels
         .zip(calendars_panel)
-        .map(|(program, calendars)| Program {
-            program_type: program,
-            calendars: calendars
-                .filter(|cal| !cal.name.to_lowercase().contains("exam")) // filter out exam calendars
-                .collect::<Vec<Calendar>>(),
+        .map(|(program, calendars)| {
+            let (exam_schedules, calendars) =
+                calendars.partition(|cal| profile.exam_pattern.is_match(&cal.name));
+            Program {
+                program_type: program,
+                calendars,
+                exam_schedules,
+            }
         })
         .collect::<Vec<Program>>();
 
-    calendars
+    Ok(calendars)
 }
 
 pub fn generate_calendar<'a>(doc: &'a Html) -> Result<CalendarDetails, Box<dyn Error + 'a>> {
-    let general_selector = Selector::parse(".row > .col-md-9").unwrap();
-    let calendar_name_selector = Selector::parse(".row > .col-md-9 h3:nth-of-type(1)").unwrap();
-    let table_selector = Selector::parse("table").unwrap();
-    let row_selector = Selector::parse("tr").unwrap();
-    let date_selector = Selector::parse("td:nth-of-type(1)").unwrap();
-    let event_selector = Selector::parse("td:nth-of-type(3)").unwrap();
-
-    let revise_date_regex = Regex::new(r"\{((\d?\d)\s(\w+)\s(\d{4}))\}").unwrap();
-    let semester_regex = Regex::new(r"(?i)(spring|summer|fall)\s(\d{4})").unwrap();
+    let profile = &ScraperProfile::global()?.calendar;
+    let date_selector = profile.column(profile.columns.date);
+    let event_selector = profile.column(profile.columns.event);
 
     let raw_doc = doc
-        .select(&general_selector)
+        .select(&profile.content)
         .next()
-        .expect("Couldn't extract raw document")
+        .ok_or("Couldn't extract raw document")?
         .text()
         .collect::<String>()
         .trim()
         .to_string();
-    let revise_date_raw = revise_date_regex
+    let revise_date_raw = profile
+        .revised_date_pattern
         .captures(&raw_doc)
-        .expect("Calendar revise date not found")[1]
+        .and_then(|x| x.get(1))
+        .ok_or("Calendar revise date not found")?
+        .as_str()
         .to_string();
 
-    let semester_capture = semester_regex
+    let semester_capture = profile
+        .semester_pattern
         .captures(&raw_doc)
-        .expect("Semester not found");
-    let semester = semester_capture[1].to_string();
-    let year = semester_capture[2]
+        .ok_or("Semester not found")?;
+    let semester = semester_capture
+        .get(1)
+        .ok_or("Semester not found")?
+        .as_str()
+        .to_string();
+    let year = semester_capture
+        .get(2)
+        .ok_or("Semester year not found")?
+        .as_str()
         .parse::<i32>()
-        .expect("Couldn't decode year");
+        .map_err(|_| "Couldn't decode year")?;
     let sem = match semester
         .split_whitespace()
         .next()
-        .expect("Error decoding semester")
+        .ok_or("Error decoding semester")?
+        .to_lowercase()
+        .as_str()
     {
-        "Spring" => Semester::Spring(year),
-        "Summer" => Semester::Summer(year),
-        "Fall" => Semester::Fall(year),
-        _ => unreachable!(),
+        "spring" => Semester::Spring(year),
+        "summer" => Semester::Summer(year),
+        "fall" => Semester::Fall(year),
+        _ => return Err("Error decoding semester".into()),
     };
 
-    let table = doc
-        .select(&table_selector)
-        .next()
-        .ok_or("dates not found")?;
+    let table = doc.select(&profile.table).next().ok_or("dates not found")?;
 
-    let revised_date = NaiveDate::parse_from_str(&revise_date_raw, "%d %B %Y")?;
+    let revised_date = NaiveDate::parse_from_str(&revise_date_raw, &profile.revised_date_format)?;
     let publish_date = match sem {
         Semester::Spring(year) => NaiveDate::from_ymd_opt(year, 1, 1).unwrap(), // rough approximations                                                                                // approximations
         Semester::Summer(year) => NaiveDate::from_ymd_opt(year, 5, 1).unwrap(),
@@ -178,7 +290,7 @@ pub fn generate_calendar<'a>(doc: &'a Html) -> Result<CalendarDetails, Box<dyn E
     };
 
     let mut entries: Vec<Entry> = vec![];
-    for row in table.select(&row_selector) {
+    for row in table.select(&profile.row) {
         let date_str = row
             .select(&date_selector)
             .map(|el| el.text().collect::<String>().trim().to_owned())
@@ -188,7 +300,7 @@ pub fn generate_calendar<'a>(doc: &'a Html) -> Result<CalendarDetails, Box<dyn E
             continue;
         }
 
-        if date_str.starts_with("Date") {
+        if profile.header_pattern.is_match(&date_str) {
             continue;
         }
 
@@ -208,11 +320,11 @@ pub fn generate_calendar<'a>(doc: &'a Html) -> Result<CalendarDetails, Box<dyn E
 
         let start_date = NaiveDate::from_ymd_opt(
             1972,
-            start_date_part.month.expect("start month is empty") as u32,
+            start_date_part.month.ok_or("start month is empty")? as u32,
             start_date_part.day as u32,
         )
-        .map(|x| with_event_year(x, publish_date))
-        .expect("invalid start date");
+        .and_then(|x| with_event_year(x, publish_date))
+        .ok_or_else(|| format!("invalid start date: {date_str}"))?;
 
         let date = match end_date_part {
             Some(end_date_part) => {
@@ -221,11 +333,11 @@ pub fn generate_calendar<'a>(doc: &'a Html) -> Result<CalendarDetails, Box<dyn E
                     end_date_part
                         .month
                         .or(start_date_part.month)
-                        .expect("both start and end month is empty") as u32,
+                        .ok_or("both start and end month is empty")? as u32,
                     end_date_part.day as u32,
                 )
-                .map(|x| with_event_year(x, publish_date))
-                .expect("invalid end date");
+                .and_then(|x| with_event_year(x, publish_date))
+                .ok_or_else(|| format!("invalid end date: {date_str}"))?;
 
                 (start_date, Some(end_date))
             }
@@ -242,11 +354,15 @@ pub fn generate_calendar<'a>(doc: &'a Html) -> Result<CalendarDetails, Box<dyn E
             })
             .collect::<String>();
 
-        entries.push(Entry { date, event });
+        entries.push(Entry {
+            date,
+            date_text: date_str,
+            event,
+        });
     }
 
     let calendar_name = doc
-        .select(&calendar_name_selector)
+        .select(&profile.name)
         .map(|el| el.text().collect::<String>().trim().to_owned())
         .collect::<String>();
 
@@ -260,57 +376,238 @@ pub fn generate_calendar<'a>(doc: &'a Html) -> Result<CalendarDetails, Box<dyn E
 }
 
 pub fn generate_ics(calendar_details: CalendarDetails) -> String {
-    let mut calendar = ICalendar::new("2.0", "icalendar");
+    generate_ics_with_options(calendar_details, &IcsOptions::default())
+}
 
-    let timezone = ICSTimeZone::standard(
-        "Asia/Dhaka",
-        Standard::new("19700101T000000", "+0600", "+0600"),
-    );
+/// Lines of an event's description: the date as written on the calendar,
+/// the public holidays it falls on and its source
+fn event_description(entry: &Entry, holidays: &[&PublicHoliday], options: &IcsOptions) -> String {
+    let mut lines = vec![];
+    if !entry.date_text.is_empty() {
+        lines.push(format!(
+            "{DATE_TEXT_LABEL}{}",
+            options.lang.date_text(&entry.date_text)
+        ));
+    }
+    if options.annotate_dates {
+        let (start, end) = (entry.start(), entry.end());
+        lines.push(options.lang.date_text(&format!(
+            "Bangabda: {}",
+            format_span(start, end, to_bangabda)
+        )));
+        lines.push(
+            options
+                .lang
+                .date_text(&format!("Hijri: {}", format_span(start, end, to_hijri))),
+        );
+    }
+    if !holidays.is_empty() {
+        let names = holidays
+            .iter()
+            .map(|x| x.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        lines.push(options.lang.text(&format!("Public holiday: {names}")));
+    }
+    if let Some(source_url) = &options.source_url {
+        lines.push(format!("Source: {source_url}"));
+    }
+    lines.join("\n")
+}
+
+/// Tags translated text with its language
+fn with_language<'a>(property: impl Into<Property<'a>>, options: &IcsOptions) -> Property<'a> {
+    let mut property = property.into();
+    if let Some(tag) = options.lang.tag() {
+        property.add(Parameter::new("LANGUAGE", tag));
+    }
+    property
+}
+
+/// Date-time in Dhaka's local time, to go with a TZID parameter
+pub(crate) fn local_date_time(date: NaiveDate, time: NaiveTime) -> String {
+    date.and_time(time).format("%Y%m%dT%H%M%S").to_string()
+}
+
+/// Marks a date-time as local to Dhaka
+pub(crate) fn with_timezone<'a>(property: impl Into<Property<'a>>) -> Property<'a> {
+    let mut property = property.into();
+    property.add(Parameter::new("TZID", TIMEZONE));
+    property
+}
+
+/// A calendar with the properties and time zone every generated calendar shares
+pub(crate) fn new_calendar<'a>(
+    name: &str,
+    description: &str,
+    options: &'a IcsOptions,
+) -> ICalendar<'a> {
+    let mut calendar = ICalendar::new("2.0", PRODID);
+
+    let timezone =
+        ICSTimeZone::standard(TIMEZONE, Standard::new("19700101T000000", "+0600", "+0600"));
     calendar.add_timezone(timezone);
+    // names and descriptions are made of the semester and dates, whose words are translated too
+    let cal_name = options.lang.date_text(name);
+    let cal_description = options.lang.date_text(description);
+    calendar.push(with_language(
+        Name::new(escape_text(cal_name.clone())),
+        options,
+    ));
+    calendar.push(with_language(
+        Property::new("X-WR-CALNAME", escape_text(cal_name)),
+        options,
+    ));
+    calendar.push(with_language(
+        Description::new(escape_text(cal_description.clone())),
+        options,
+    ));
+    calendar.push(with_language(
+        Property::new("X-WR-CALDESC", escape_text(cal_description)),
+        options,
+    ));
+    calendar.push(Property::new("X-WR-TIMEZONE", TIMEZONE));
+    calendar.push(RefreshInterval::new(REFRESH_INTERVAL));
+    calendar.push(Property::new("X-PUBLISHED-TTL", REFRESH_INTERVAL));
+    if !options.color.is_empty() {
+        calendar.push(Color::new(&options.color));
+    }
+    if let Some(feed_url) = &options.feed_url {
+        calendar.push(Source::new(feed_url));
+    }
+    calendar.push(CalScale::new("GREGORIAN"));
+    calendar.push(Method::new("PUBLISH"));
+    calendar
+}
+
+/// Serializes a calendar, folding its lines within 75 octets
+pub(crate) fn to_ics(calendar: ICalendar) -> String {
+    // the ics crate lets continuation lines reach 76 octets, so they're folded again
+    ical::unfold(&calendar.to_string())
+        .iter()
+        .map(|line| ical::fold(line) + "\r\n")
+        .collect()
+}
+
+pub fn generate_ics_with_options(
+    calendar_details: CalendarDetails,
+    options: &IcsOptions,
+) -> String {
     let cal_name = format!(
         "{} {} {}",
         calendar_details.semester, calendar_details.year, calendar_details.calendar_name
     );
-    calendar.push(Name::new(&cal_name));
-    calendar.push(Property::new("X-WR-CALNAME", &cal_name));
-    calendar.push(CalScale::new("GREGORIAN"));
-    calendar.push(Method::new("PUBLISH"));
+    let cal_description = format!(
+        "East West University academic calendar of {cal_name}, revised {}",
+        calendar_details.revised_date.format("%-d %B %Y")
+    );
+    let mut calendar = new_calendar(&cal_name, &cal_description, options);
 
     let entries = calendar_details.entries;
+    let mut uids = HashSet::new();
 
     for entry in entries {
-        let ev_hash = xxhash_rust::xxh3::xxh3_64(entry.event.as_bytes());
+        // events sharing a title, eg: Holiday, are told apart by their date,
+        // so reordering rows keeps UIDs. Repeated rows take the next free one
+        let mut ev_hash =
+            xxhash_rust::xxh3::xxh3_64(format!("{} {}", entry.event, entry.start()).as_bytes());
+        while !uids.insert(ev_hash) {
+            ev_hash = ev_hash.wrapping_add(1);
+        }
         let mut event = Event::new(
             format!("{:x}", ev_hash),
-            Utc::now().format("%Y%m%dT000000").to_string(),
+            Utc::now().format("%Y%m%dT000000Z").to_string(),
         );
-        let mut dtstart = DtStart::new(entry.date.0.format("%Y%m%d").to_string());
+        let mut dtstart = DtStart::new(entry.start().format("%Y%m%d").to_string());
         dtstart.add(Parameter::new("VALUE", "DATE"));
         event.push(dtstart);
 
-        let mut dtend = DtEnd::new(
-            entry
-                .date
-                .1
-                .or(Some(entry.date.0))
-                .map(|date| date.format("%Y%m%d").to_string())
-                .unwrap(),
-        );
+        // DTEND of all day events is exclusive, so it is the day after the last day
+        let mut dtend = DtEnd::new((entry.end() + Days::new(1)).format("%Y%m%d").to_string());
         dtend.add(Parameter::new("VALUE", "DATE"));
         event.push(dtend);
 
         event.push(LastModified::new(
             calendar_details
                 .revised_date
-                .format("%Y%m%dT000000")
+                .format("%Y%m%dT000000Z")
                 .to_string(),
         ));
-        event.push(Summary::new(entry.event));
-        event.push(Location::new("East West University, Dhaka"));
+
+        let holidays = official_holidays(&entry);
+        if !holidays.is_empty() {
+            event.push(Categories::new(PUBLIC_HOLIDAY_CATEGORY));
+        }
+        let description = event_description(&entry, &holidays, options);
+        if !description.is_empty() {
+            event.push(with_language(
+                Description::new(escape_text(description)),
+                options,
+            ));
+        }
+        if let Some(source_url) = &options.source_url {
+            event.push(URL::new(source_url));
+        }
+        if let Some(organizer) = &options.organizer {
+            let mut organizer = Organizer::new(format!("mailto:{organizer}"));
+            organizer.add(Parameter::new("CN", ORGANIZER_NAME));
+            event.push(organizer);
+        }
+        let category = Category::classify(&entry.event);
+        if let Some(location) = options.location_for(category) {
+            event.push(Location::new(escape_text(location)));
+            if let Some((latitude, longitude)) = options.geo {
+                event.push(Geo::new(format!("{latitude:.6};{longitude:.6}")));
+            }
+        }
+
+        // all day events would otherwise block out whole days, or weeks for breaks
+        let busy = options.busy.contains(&category);
+        event.push(Transp::new(if busy { "OPAQUE" } else { "TRANSPARENT" }));
+        event.push(Property::new(
+            "X-MICROSOFT-CDO-BUSYSTATUS",
+            if busy { "BUSY" } else { "FREE" },
+        ));
+
+        let title = options.lang.text(&entry.event);
+        for reminder in options.alarms_for(category) {
+            let mut description = Description::new(escape_text(title.clone()));
+            if let Some(tag) = options.lang.tag() {
+                description.add(Parameter::new("LANGUAGE", tag));
+            }
+            event.add_alarm(Alarm::display(
+                Trigger::new(reminder.trigger()),
+                description,
+            ));
+        }
+
+        event.push(with_language(Summary::new(escape_text(title)), options));
         calendar.add_event(event);
     }
 
-    calendar.to_string()
+    to_ics(calendar)
 }
 
 // TODO: implement SEQUENCE property
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{calendar, date, entry};
+
+    #[test]
+    fn test_annotated_calendar_details() {
+        let calendar = calendar(vec![entry(date(3, 26), None, "Independence Day (Holiday)")]);
+
+        let entry = serde_json::to_value(&calendar.entries[0]).unwrap();
+        assert!(entry.get("bangabda").is_none(), "{entry}");
+
+        // holidays are still found when the text is translated
+        let annotated = AnnotatedCalendarDetails::new(&calendar, Lang::Bn);
+        let entry = &annotated.entries[0];
+        assert_ne!(entry.event, "Independence Day (Holiday)");
+        assert_eq!(entry.official_holidays, vec!["Independence Day"]);
+        assert_eq!(entry.bangabda.0.to_string(), "12 Choitro 1430");
+        assert_eq!(entry.date, (date(3, 26), None));
+    }
+}
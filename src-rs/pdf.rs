@@ -0,0 +1,372 @@
+//! Printable semester planner: one month grid per page,
+//! written as a plain PDF 1.4 document using the standard Helvetica fonts.
+//! Those only cover the Latin script, so text in others, eg: Bengali, is printed as `?`
+//! and a note on the page points to the online calendar
+
+use chrono::{Datelike, Months, NaiveDate, Weekday};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::category::Category;
+use crate::parser::CalendarDetails;
+
+// A4 landscape, in points
+const PAGE_WIDTH: f32 = 842.0;
+const PAGE_HEIGHT: f32 = 595.0;
+const MARGIN: f32 = 36.0;
+const GRID_TOP: f32 = PAGE_HEIGHT - 90.0;
+const HEADER_HEIGHT: f32 = 18.0;
+const EVENT_FONT_SIZE: f32 = 6.5;
+const EVENT_LINE_HEIGHT: f32 = 8.0;
+
+const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Sun,
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+];
+
+/// Fill color of a day, by the most important category of its events
+fn shade(categories: &[Category]) -> Option<(f32, f32, f32)> {
+    if categories.contains(&Category::Holiday) {
+        Some((0.85, 0.85, 0.85))
+    } else if categories.contains(&Category::Exam) {
+        Some((1.0, 0.84, 0.84))
+    } else if categories.contains(&Category::Deadline) {
+        Some((1.0, 0.93, 0.55))
+    } else {
+        None
+    }
+}
+
+const UNSUPPORTED_NOTE: &str =
+    "Some text can't be printed in this planner and is shown as ?. See the online calendar for it";
+
+/// Code of a character in WinAnsiEncoding, the encoding of the standard fonts
+fn win_ansi(c: char) -> Option<u8> {
+    match c {
+        ' '..='~' | '\u{a0}'..='\u{ff}' => Some(c as u8),
+        '\u{20ac}' => Some(0x80),
+        '\u{2026}' => Some(0x85),
+        '\u{2018}' => Some(0x91),
+        '\u{2019}' => Some(0x92),
+        '\u{201c}' => Some(0x93),
+        '\u{201d}' => Some(0x94),
+        '\u{2022}' => Some(0x95),
+        '\u{2013}' => Some(0x96),
+        '\u{2014}' => Some(0x97),
+        _ => None,
+    }
+}
+
+fn is_printable(text: &str) -> bool {
+    text.chars().all(|c| win_ansi(c).is_some())
+}
+
+/// Escapes a string for a PDF literal, replacing what WinAnsi can't encode with `?`
+fn pdf_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match win_ansi(c) {
+            Some(b'(' | b')' | b'\\') => {
+                out.push('\\');
+                out.push(c);
+            }
+            Some(code @ b' '..=b'~') => out.push(code as char),
+            // the content stream stays ASCII, so other codes are written as octal escapes
+            Some(code) => {
+                let _ = write!(out, "\\{code:03o}");
+            }
+            None => out.push('?'),
+        }
+    }
+    out
+}
+
+/// Greedy word wrap by an average Helvetica glyph width
+fn wrap(text: &str, width: f32, font_size: f32) -> Vec<String> {
+    let max_chars = (width / (font_size * 0.5)) as usize;
+    let mut lines: Vec<String> = vec![];
+    for word in text.split_whitespace() {
+        match lines.last_mut() {
+            Some(line) if line.len() + 1 + word.len() <= max_chars => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(word.chars().take(max_chars).collect()),
+        }
+    }
+    lines
+}
+
+fn text_at(content: &mut String, font: &str, size: f32, x: f32, y: f32, text: &str) {
+    let _ = writeln!(
+        content,
+        "BT /{font} {size} Tf {x:.1} {y:.1} Td ({}) Tj ET",
+        pdf_text(text)
+    );
+}
+
+fn month_page(
+    calendar: &CalendarDetails,
+    month_start: NaiveDate,
+    days: &HashMap<NaiveDate, Vec<(Category, &str)>>,
+) -> String {
+    let mut content = String::new();
+    let cell_width = (PAGE_WIDTH - 2.0 * MARGIN) / 7.0;
+    let cell_height = (GRID_TOP - HEADER_HEIGHT - MARGIN) / 6.0;
+
+    let title = format!(
+        "{} {} {}",
+        calendar.semester, calendar.year, calendar.calendar_name
+    );
+    text_at(&mut content, "F2", 16.0, MARGIN, PAGE_HEIGHT - 50.0, &title);
+    let month_title = month_start.format("%B %Y").to_string();
+    text_at(
+        &mut content,
+        "F2",
+        13.0,
+        MARGIN,
+        PAGE_HEIGHT - 72.0,
+        &month_title,
+    );
+
+    let legend = [
+        ("Holiday", (0.85, 0.85, 0.85)),
+        ("Exam", (1.0, 0.84, 0.84)),
+        ("Deadline", (1.0, 0.93, 0.55)),
+    ];
+    for (i, (label, (r, g, b))) in legend.iter().enumerate() {
+        let x = PAGE_WIDTH - MARGIN - 240.0 + i as f32 * 80.0;
+        let _ = writeln!(
+            content,
+            "{r} {g} {b} rg {x:.1} {:.1} 10 10 re f 0 g",
+            PAGE_HEIGHT - 73.0
+        );
+        text_at(&mut content, "F1", 9.0, x + 14.0, PAGE_HEIGHT - 72.0, label);
+    }
+
+    for (column, weekday) in WEEKDAYS.iter().enumerate() {
+        let x = MARGIN + column as f32 * cell_width;
+        text_at(
+            &mut content,
+            "F2",
+            9.0,
+            x + 4.0,
+            GRID_TOP - 13.0,
+            &weekday.to_string(),
+        );
+    }
+
+    let offset = month_start.weekday().num_days_from_sunday() as usize;
+    let month_days = month_start
+        .iter_days()
+        .take_while(|date| date.month() == month_start.month());
+    for date in month_days {
+        let index = offset + date.day0() as usize;
+        let (row, column) = (index / 7, index % 7);
+        let x = MARGIN + column as f32 * cell_width;
+        let top = GRID_TOP - HEADER_HEIGHT - row as f32 * cell_height;
+
+        let events = days.get(&date).map(Vec::as_slice).unwrap_or_default();
+        let categories = events
+            .iter()
+            .map(|(category, _)| *category)
+            .collect::<Vec<_>>();
+        if let Some((r, g, b)) = shade(&categories) {
+            let _ = writeln!(
+                content,
+                "{r} {g} {b} rg {x:.1} {:.1} {cell_width:.1} {cell_height:.1} re f 0 g",
+                top - cell_height
+            );
+        }
+        let _ = writeln!(
+            content,
+            "0.5 w {x:.1} {:.1} {cell_width:.1} {cell_height:.1} re S",
+            top - cell_height
+        );
+
+        text_at(
+            &mut content,
+            "F2",
+            9.0,
+            x + 4.0,
+            top - 11.0,
+            &date.day().to_string(),
+        );
+
+        let max_lines = ((cell_height - 16.0) / EVENT_LINE_HEIGHT) as usize;
+        let lines = events
+            .iter()
+            .flat_map(|(_, event)| wrap(event, cell_width - 8.0, EVENT_FONT_SIZE))
+            .collect::<Vec<_>>();
+        for (i, line) in lines.iter().take(max_lines).enumerate() {
+            let line = if i + 1 == max_lines && lines.len() > max_lines {
+                "..."
+            } else {
+                line.as_str()
+            };
+            let y = top - 20.0 - i as f32 * EVENT_LINE_HEIGHT;
+            text_at(&mut content, "F1", EVENT_FONT_SIZE, x + 4.0, y, line);
+        }
+    }
+
+    let mut month_events = days
+        .iter()
+        .filter(|(date, _)| {
+            date.year() == month_start.year() && date.month() == month_start.month()
+        })
+        .flat_map(|(_, events)| events.iter().map(|(_, event)| *event));
+    if !is_printable(&title) || !month_events.all(is_printable) {
+        text_at(
+            &mut content,
+            "F1",
+            8.0,
+            MARGIN,
+            MARGIN / 2.0,
+            UNSUPPORTED_NOTE,
+        );
+    }
+
+    content
+}
+
+/// Renders a month grid page for every month the calendar's events touch
+pub fn render(calendar: &CalendarDetails) -> Vec<u8> {
+    let mut days: HashMap<NaiveDate, Vec<(Category, &str)>> = HashMap::new();
+    for entry in &calendar.entries {
+        let category = Category::classify(&entry.event);
+        for date in entry.start().iter_days().take(entry.days().max(1) as usize) {
+            days.entry(date)
+                .or_default()
+                .push((category, entry.event.as_str()));
+        }
+    }
+
+    let first = calendar.entries.iter().map(|x| x.start()).min();
+    let last = calendar.entries.iter().map(|x| x.end()).max();
+
+    let mut pages = vec![];
+    if let (Some(first), Some(last)) = (first, last) {
+        let mut month = first.with_day(1).unwrap_or(first);
+        while month <= last {
+            pages.push(month_page(calendar, month, &days));
+            month = match month.checked_add_months(Months::new(1)) {
+                Some(next) => next,
+                None => break,
+            };
+        }
+    }
+
+    write_document(&pages)
+}
+
+/// Assembles page content streams into a PDF file
+fn write_document(pages: &[String]) -> Vec<u8> {
+    // objects 1-4 are the catalog, page tree and fonts, then a page and its content per page
+    let page_ids = (0..pages.len()).map(|i| 5 + i * 2).collect::<Vec<_>>();
+    let kids = page_ids
+        .iter()
+        .map(|id| format!("{id} 0 R"))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        format!(
+            "<< /Type /Pages /Kids [{kids}] /Count {} /MediaBox [0 0 {PAGE_WIDTH} {PAGE_HEIGHT}] >>",
+            pages.len()
+        ),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+            .to_string(),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>"
+            .to_string(),
+    ];
+    for (page_id, content) in page_ids.iter().zip(pages) {
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+            page_id + 1
+        ));
+        objects.push(format!(
+            "<< /Length {} >>\nstream\n{content}endstream",
+            content.len()
+        ));
+    }
+
+    let mut out = String::from("%PDF-1.4\n");
+    let mut offsets = vec![];
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(out.len());
+        let _ = write!(out, "{} 0 obj\n{object}\nendobj\n", i + 1);
+    }
+
+    let xref = out.len();
+    let _ = write!(out, "xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+    for offset in offsets {
+        let _ = writeln!(out, "{offset:010} 00000 n ");
+    }
+    let _ = write!(
+        out,
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+        objects.len() + 1
+    );
+
+    out.into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{calendar, date, entry};
+
+    #[test]
+    fn test_render() {
+        let calendar = calendar(vec![
+            entry(date(1, 7), None, "Classes begin"),
+            entry(date(3, 26), None, "Independence Day (Holiday)"),
+        ]);
+
+        let pdf = String::from_utf8(render(&calendar)).unwrap();
+
+        assert!(pdf.starts_with("%PDF-1.4\n"));
+        assert!(pdf.ends_with("%%EOF\n"));
+        assert!(pdf.contains("/Count 3"));
+        assert!(pdf.contains("(January 2024) Tj"));
+        assert!(pdf.contains("(Independence Day \\(Holiday\\)) Tj"));
+        assert!(pdf.contains("0.85 0.85 0.85 rg"));
+        assert!(!pdf.contains(UNSUPPORTED_NOTE));
+
+        // every xref offset must point at the start of its object
+        let xref_start = pdf.find("xref\n").unwrap();
+        let offsets = pdf[xref_start..]
+            .lines()
+            .skip(3)
+            .take_while(|line| line.ends_with(" n "))
+            .map(|line| line[..10].parse::<usize>().unwrap());
+        for (i, offset) in offsets.enumerate() {
+            assert!(pdf[offset..].starts_with(&format!("{} 0 obj", i + 1)));
+        }
+    }
+
+    #[test]
+    fn test_non_ascii_text() {
+        assert_eq!(
+            pdf_text("Café \u{2013} (note)"),
+            "Caf\\351 \\226 \\(note\\)"
+        );
+        assert_eq!(pdf_text("ঈদ Eid"), "?? Eid");
+
+        let calendar = calendar(vec![
+            entry(date(1, 7), None, "Classes begin"),
+            entry(date(2, 21), None, "শহীদ দিবস (Holiday)"),
+        ]);
+        let pdf = String::from_utf8(render(&calendar)).unwrap();
+
+        // only February's page has the unprintable text
+        assert_eq!(pdf.matches(UNSUPPORTED_NOTE).count(), 1);
+        assert!(pdf.is_ascii());
+    }
+}
@@ -0,0 +1,151 @@
+//! Human readable renderings of a calendar for wikis, chats and the web
+
+use chrono::Datelike;
+use std::fmt::Write;
+
+use crate::parser::{CalendarDetails, Entry};
+use crate::xcal::escape_xml;
+
+fn title(calendar: &CalendarDetails) -> String {
+    format!(
+        "{} {} {}",
+        calendar.semester, calendar.year, calendar.calendar_name
+    )
+}
+
+/// eg: Tue, 09 Apr or Tue, 09 Apr - Sat, 13 Apr
+fn date_range(entry: &Entry) -> String {
+    let start = entry.start().format("%a, %d %b");
+    if entry.days() > 1 {
+        format!("{} - {}", start, entry.end().format("%a, %d %b"))
+    } else {
+        start.to_string()
+    }
+}
+
+pub fn markdown(calendar: &CalendarDetails) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "## {}\n", title(calendar));
+    let _ = writeln!(
+        out,
+        "_Last revised {}_\n",
+        calendar.revised_date.format("%d %B %Y")
+    );
+    out.push_str("| Date | Event |\n| --- | --- |\n");
+
+    for entry in &calendar.entries {
+        let event = entry.event.replace('|', "\\|").replace('\n', " ");
+        let _ = writeln!(out, "| {} | {} |", date_range(entry), event);
+    }
+
+    out
+}
+
+pub fn html(calendar: &CalendarDetails) -> String {
+    let title = escape_xml(&title(calendar));
+    let mut rows = String::new();
+
+    for entry in &calendar.entries {
+        let _ = writeln!(
+            rows,
+            "      <tr><td>{}</td><td>{}</td></tr>",
+            escape_xml(&date_range(entry)),
+            escape_xml(&entry.event)
+        );
+    }
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+  <head>
+    <meta charset="utf-8" />
+    <title>{title}</title>
+    <style>
+      body {{ font-family: sans-serif; margin: 2em; }}
+      table {{ border-collapse: collapse; }}
+      td, th {{ border: 1px solid #aaa; padding: 0.3em 0.6em; text-align: left; }}
+    </style>
+  </head>
+  <body>
+    <h1>{title}</h1>
+    <p>Last revised {revised}</p>
+    <table>
+      <tr><th>Date</th><th>Event</th></tr>
+{rows}    </table>
+  </body>
+</html>
+"#,
+        revised = calendar.revised_date.format("%d %B %Y"),
+    )
+}
+
+/// Plain text list of events, grouped under the month they start in
+pub fn agenda(calendar: &CalendarDetails) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "{}", title(calendar));
+    let _ = writeln!(
+        out,
+        "Last revised {}",
+        calendar.revised_date.format("%d %B %Y")
+    );
+
+    let mut current_month = None;
+    for entry in &calendar.entries {
+        let month = (entry.start().year(), entry.start().month());
+        if current_month != Some(month) {
+            current_month = Some(month);
+            let _ = writeln!(out, "\n{}", entry.start().format("%B %Y"));
+        }
+        let _ = writeln!(out, "  {:<27}{}", date_range(entry), entry.event);
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{date, entry};
+
+    fn calendar() -> CalendarDetails {
+        crate::test_util::calendar(vec![
+            entry(date(1, 7), None, "Classes begin"),
+            entry(date(4, 9), Some(date(4, 13)), "Eid-ul-Fitr | <no classes>"),
+        ])
+    }
+
+    #[test]
+    fn test_markdown() {
+        assert_eq!(
+            markdown(&calendar()),
+            "## Spring 2024 Undergraduate\n\n\
+             _Last revised 02 January 2024_\n\n\
+             | Date | Event |\n\
+             | --- | --- |\n\
+             | Sun, 07 Jan | Classes begin |\n\
+             | Tue, 09 Apr - Sat, 13 Apr | Eid-ul-Fitr \\| <no classes> |\n"
+        );
+    }
+
+    #[test]
+    fn test_html_is_escaped() {
+        let page = html(&calendar());
+        assert!(page.contains("<title>Spring 2024 Undergraduate</title>"));
+        assert!(page.contains("<td>Eid-ul-Fitr | &lt;no classes&gt;</td>"));
+    }
+
+    #[test]
+    fn test_agenda() {
+        assert_eq!(
+            agenda(&calendar()),
+            "Spring 2024 Undergraduate\n\
+             Last revised 02 January 2024\n\
+             \n\
+             January 2024\n  \
+             Sun, 07 Jan                Classes begin\n\
+             \n\
+             April 2024\n  \
+             Tue, 09 Apr - Sat, 13 Apr  Eid-ul-Fitr | <no classes>\n"
+        );
+    }
+}
@@ -0,0 +1,299 @@
+//! Class routines: a student's weekly class sessions as recurring events,
+//! running from the first to the last day of classes and skipping holidays
+
+use chrono::{prelude::*, Duration};
+use ics::{
+    components::Property,
+    escape_text,
+    properties::{
+        Description, DtEnd, DtStart, ExDate, Geo, LastModified, Location, RRule, Summary, Transp,
+        Trigger, URL,
+    },
+    Alarm, Event,
+};
+use serde::{Deserialize, Deserializer};
+use std::collections::HashSet;
+
+use crate::category::Category;
+use crate::error::ApiError;
+use crate::options::IcsOptions;
+use crate::parser::{local_date_time, new_calendar, to_ics, with_timezone, CalendarDetails};
+use crate::stats::{holiday_dates, teaching_span};
+
+/// Offset of Asia/Dhaka from UTC, which has no daylight saving time
+const UTC_OFFSET_HOURS: i64 = 6;
+
+/// A course section a student takes, as written on their advising slip
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Section {
+    /// Course code, eg: CSE110
+    #[serde(alias = "code")]
+    pub course: String,
+    /// Class days, eg: ST for Sunday and Tuesday
+    #[serde(deserialize_with = "deserialize_days")]
+    pub days: Vec<Weekday>,
+    #[serde(deserialize_with = "deserialize_time")]
+    pub start: NaiveTime,
+    #[serde(deserialize_with = "deserialize_time")]
+    pub end: NaiveTime,
+    #[serde(default)]
+    pub room: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Routine {
+    sections: Vec<Section>,
+}
+
+/// Parses EWU's day letters: S, M, T, W, R for Thursday, F and A for Saturday
+pub fn parse_days(days: &str) -> Result<Vec<Weekday>, ApiError> {
+    let mut weekdays = vec![];
+    for letter in days.chars().filter(|c| !c.is_whitespace()) {
+        let weekday = match letter.to_ascii_uppercase() {
+            'S' => Weekday::Sun,
+            'M' => Weekday::Mon,
+            'T' => Weekday::Tue,
+            'W' => Weekday::Wed,
+            'R' => Weekday::Thu,
+            'F' => Weekday::Fri,
+            'A' => Weekday::Sat,
+            _ => {
+                return Err(ApiError::BadRequest(format!(
+                    "Invalid day \"{letter}\" in \"{days}\", expected one of S, M, T, W, R, F, A"
+                )))
+            }
+        };
+        if !weekdays.contains(&weekday) {
+            weekdays.push(weekday);
+        }
+    }
+
+    if weekdays.is_empty() {
+        return Err(ApiError::BadRequest(
+            "Section has no class days".to_string(),
+        ));
+    }
+    Ok(weekdays)
+}
+
+/// Parses a time of day, eg: 08:30, 13:00 or 1:00 PM
+pub fn parse_time(time: &str) -> Result<NaiveTime, ApiError> {
+    let time = time.trim();
+    ["%H:%M", "%I:%M %p", "%I:%M%p"]
+        .iter()
+        .find_map(|format| NaiveTime::parse_from_str(time, format).ok())
+        .ok_or_else(|| ApiError::BadRequest(format!("Invalid time \"{time}\", expected eg: 08:30")))
+}
+
+fn deserialize_days<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Weekday>, D::Error> {
+    let days = String::deserialize(deserializer)?;
+    parse_days(&days).map_err(|e| serde::de::Error::custom(e.detail()))
+}
+
+fn deserialize_time<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NaiveTime, D::Error> {
+    let time = String::deserialize(deserializer)?;
+    parse_time(&time).map_err(|e| serde::de::Error::custom(e.detail()))
+}
+
+/// Reads the sections of a routine from a JSON body, eg:
+/// `{"sections": [{"course": "CSE110", "days": "ST", "start": "08:30", "end": "10:00"}]}`
+pub fn parse_sections(body: &[u8]) -> Result<Vec<Section>, ApiError> {
+    let routine: Routine =
+        serde_json::from_slice(body).map_err(|e| ApiError::BadRequest(e.to_string()))?;
+
+    if routine.sections.is_empty() {
+        return Err(ApiError::BadRequest("Routine has no sections".to_string()));
+    }
+    for section in &routine.sections {
+        if section.end <= section.start {
+            return Err(ApiError::BadRequest(format!(
+                "{} ends at {}, before it starts at {}",
+                section.course, section.end, section.start
+            )));
+        }
+    }
+
+    Ok(routine.sections)
+}
+
+/// Generates the weekly class sessions of the sections within the semester's
+/// teaching span, leaving out days the calendar marks as holidays
+pub fn generate_routine_ics(
+    calendar_details: &CalendarDetails,
+    sections: &[Section],
+    options: &IcsOptions,
+) -> Result<String, ApiError> {
+    let (classes_start, classes_end) = teaching_span(calendar_details)?;
+    let holidays = holiday_dates(calendar_details);
+
+    let cal_name = format!(
+        "{} {} {} class routine",
+        calendar_details.semester, calendar_details.year, calendar_details.calendar_name
+    );
+    let cal_description = format!(
+        "Weekly classes of {} {}, from {} to {}",
+        calendar_details.semester,
+        calendar_details.year,
+        classes_start.format("%-d %B"),
+        classes_end.format("%-d %B %Y")
+    );
+    let mut calendar = new_calendar(&cal_name, &cal_description, options);
+
+    // UNTIL must be in UTC when DTSTART has a time zone, so it's the end of the last day in Dhaka
+    let until = classes_end.and_hms_opt(23, 59, 59).unwrap() - Duration::hours(UTC_OFFSET_HOURS);
+    let until = until.format("%Y%m%dT%H%M%SZ");
+
+    let mut uids = HashSet::new();
+    for section in sections {
+        let Some(first_class) = classes_start
+            .iter_days()
+            .take_while(|date| *date <= classes_end)
+            .find(|date| section.days.contains(&date.weekday()))
+        else {
+            continue;
+        };
+
+        let by_day = section
+            .days
+            .iter()
+            .map(|weekday| weekday.to_string()[..2].to_uppercase())
+            .collect::<Vec<_>>()
+            .join(",");
+        let mut ev_hash = xxhash_rust::xxh3::xxh3_64(
+            format!("{cal_name} {} {by_day} {}", section.course, section.start).as_bytes(),
+        );
+        while !uids.insert(ev_hash) {
+            ev_hash = ev_hash.wrapping_add(1);
+        }
+        let mut event = Event::new(
+            format!("{:x}", ev_hash),
+            Utc::now().format("%Y%m%dT000000Z").to_string(),
+        );
+        event.push(with_timezone(DtStart::new(local_date_time(
+            first_class,
+            section.start,
+        ))));
+        event.push(with_timezone(DtEnd::new(local_date_time(
+            first_class,
+            section.end,
+        ))));
+        event.push(RRule::new(format!(
+            "FREQ=WEEKLY;UNTIL={until};BYDAY={by_day}"
+        )));
+
+        let mut cancelled = holidays
+            .iter()
+            .filter(|date| {
+                (classes_start..=classes_end).contains(*date)
+                    && section.days.contains(&date.weekday())
+            })
+            .collect::<Vec<_>>();
+        cancelled.sort();
+        if !cancelled.is_empty() {
+            let dates = cancelled
+                .iter()
+                .map(|date| local_date_time(**date, section.start))
+                .collect::<Vec<_>>()
+                .join(",");
+            event.push(with_timezone(ExDate::new(dates)));
+        }
+
+        event.push(LastModified::new(
+            calendar_details
+                .revised_date
+                .format("%Y%m%dT000000Z")
+                .to_string(),
+        ));
+        if let Some(source_url) = &options.source_url {
+            event.push(URL::new(source_url));
+        }
+        let location = section
+            .room
+            .as_deref()
+            .or(options.location_for(Category::Classes));
+        if let Some(location) = location {
+            event.push(Location::new(escape_text(location)));
+            if let Some((latitude, longitude)) = options.geo {
+                event.push(Geo::new(format!("{latitude:.6};{longitude:.6}")));
+            }
+        }
+
+        // unlike the all day events of the calendar, a class takes up its time
+        event.push(Transp::new("OPAQUE"));
+        event.push(Property::new("X-MICROSOFT-CDO-BUSYSTATUS", "BUSY"));
+
+        for reminder in options.alarms_for(Category::Classes) {
+            event.add_alarm(Alarm::display(
+                Trigger::new(reminder.trigger()),
+                Description::new(escape_text(section.course.clone())),
+            ));
+        }
+
+        event.push(Summary::new(escape_text(section.course.clone())));
+        calendar.add_event(event);
+    }
+
+    Ok(to_ics(calendar))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conformance::validate;
+    use crate::test_util::{date, entry};
+
+    fn calendar() -> CalendarDetails {
+        crate::test_util::calendar(vec![
+            entry(date(1, 7), None, "Classes begin"),
+            entry(date(1, 9), Some(date(1, 10)), "Holiday"),
+            entry(date(1, 14), None, "Shab-e-Barat (Holiday)"),
+            entry(date(1, 18), None, "Last day of classes"),
+        ])
+    }
+
+    #[test]
+    fn test_parse_sections() {
+        let sections = parse_sections(
+            br#"{"sections": [{"code": "CSE110", "days": "TR", "start": "1:00 PM", "end": "14:30", "room": "AB3-302"}]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            sections,
+            vec![Section {
+                course: "CSE110".to_string(),
+                days: vec![Weekday::Tue, Weekday::Thu],
+                start: NaiveTime::from_hms_opt(13, 0, 0).unwrap(),
+                end: NaiveTime::from_hms_opt(14, 30, 0).unwrap(),
+                room: Some("AB3-302".to_string()),
+            }]
+        );
+        assert!(parse_sections(br#"{"sections": []}"#).is_err());
+        assert!(parse_days("SX").is_err());
+        assert!(
+            parse_sections(br#"{"sections": [{"course": "MAT101", "days": "MW", "start": "10:00", "end": "08:30"}]}"#)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_generate_routine_ics() {
+        let sections = vec![Section {
+            course: "CSE110".to_string(),
+            days: parse_days("ST").unwrap(),
+            start: parse_time("08:30").unwrap(),
+            end: parse_time("10:00").unwrap(),
+            room: Some("AB3-302".to_string()),
+        }];
+        let ics = generate_routine_ics(&calendar(), &sections, &IcsOptions::default()).unwrap();
+
+        assert_eq!(validate(ics.as_bytes()), vec![]);
+        let lines = ics.lines().collect::<Vec<_>>();
+        assert!(lines.contains(&"DTSTART;TZID=Asia/Dhaka:20240107T083000"));
+        assert!(lines.contains(&"DTEND;TZID=Asia/Dhaka:20240107T100000"));
+        assert!(lines.contains(&"RRULE:FREQ=WEEKLY;UNTIL=20240118T175959Z;BYDAY=SU,TU"));
+        // the Wednesday holiday has no class to cancel
+        assert!(lines.contains(&"EXDATE;TZID=Asia/Dhaka:20240109T083000,20240114T083000"));
+        assert!(lines.contains(&"LOCATION:AB3-302"));
+    }
+}
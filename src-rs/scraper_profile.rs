@@ -0,0 +1,289 @@
+//! Where the scraper finds things on ewubd.edu: selectors, table columns and patterns.
+//! The bundled profile matches the current site; after a redesign, a JSON file named by
+//! `EWU_SCRAPER_PROFILE` can override any part of it without recompiling
+
+use regex::Regex;
+use scraper::Selector;
+use serde::Deserialize;
+use serde_json::Value;
+use std::env;
+use std::error::Error;
+use std::ffi::OsString;
+use std::fs;
+use std::num::NonZeroUsize;
+use std::ops::Deref;
+use std::sync::OnceLock;
+
+const DEFAULT_PROFILE: &str = include_str!("data/scraper_profile.json");
+
+/// A CSS selector, checked when the profile is loaded
+#[derive(Deserialize, Debug, Clone)]
+#[serde(try_from = "String")]
+pub struct CssSelector(Selector);
+
+impl TryFrom<String> for CssSelector {
+    type Error = String;
+
+    fn try_from(selector: String) -> Result<Self, Self::Error> {
+        Selector::parse(&selector)
+            .map(CssSelector)
+            .map_err(|e| format!("invalid selector \"{selector}\": {e:?}"))
+    }
+}
+
+impl Deref for CssSelector {
+    type Target = Selector;
+
+    fn deref(&self) -> &Selector {
+        &self.0
+    }
+}
+
+/// A CSS selector with a `{year}` placeholder, eg: `[id="{year}"]`
+#[derive(Deserialize, Debug, Clone)]
+#[serde(try_from = "String")]
+pub struct YearSelector(String);
+
+impl YearSelector {
+    pub fn for_year(&self, year: &str) -> Option<Selector> {
+        Selector::parse(&self.0.replace("{year}", year)).ok()
+    }
+}
+
+impl TryFrom<String> for YearSelector {
+    type Error = String;
+
+    fn try_from(selector: String) -> Result<Self, Self::Error> {
+        let selector = YearSelector(selector);
+        match selector.for_year("2024") {
+            Some(_) => Ok(selector),
+            None => Err(format!("invalid selector \"{}\"", selector.0)),
+        }
+    }
+}
+
+/// A regular expression, checked when the profile is loaded
+#[derive(Deserialize, Debug, Clone)]
+#[serde(try_from = "String")]
+pub struct Pattern(Regex);
+
+impl TryFrom<String> for Pattern {
+    type Error = String;
+
+    fn try_from(pattern: String) -> Result<Self, Self::Error> {
+        Regex::new(&pattern)
+            .map(Pattern)
+            .map_err(|e| format!("invalid pattern \"{pattern}\": {e}"))
+    }
+}
+
+impl Pattern {
+    /// Rejects a pattern with fewer than `groups` capture groups, as its users index them
+    fn check_groups(&self, name: &str, groups: usize) -> Result<(), String> {
+        if self.captures_len() > groups {
+            Ok(())
+        } else {
+            Err(format!("{name} needs {groups} capture group(s)"))
+        }
+    }
+}
+
+impl Deref for Pattern {
+    type Target = Regex;
+
+    fn deref(&self) -> &Regex {
+        &self.0
+    }
+}
+
+/// The academic calendar page listing every calendar by year and program
+#[derive(Deserialize, Debug, Clone)]
+pub struct ListingProfile {
+    /// Tabs naming the years
+    pub year: CssSelector,
+    /// Tab holding a year's programs
+    pub year_tab: YearSelector,
+    /// Names of the programs within a year's tab
+    pub program_name: CssSelector,
+    /// Calendar lists of the programs, in the same order as their names
+    pub program_body: CssSelector,
+    /// Links to the calendars of a program
+    pub calendar_link: CssSelector,
+    /// Matches names of exam schedules, which are listed apart from the calendars
+    pub exam_pattern: Pattern,
+}
+
+/// 1-based positions of the columns of the calendar table
+#[derive(Deserialize, Debug, Clone)]
+pub struct CalendarColumns {
+    pub date: NonZeroUsize,
+    pub event: NonZeroUsize,
+}
+
+/// A calendar page. Exam schedule pages share its content, name and semester
+#[derive(Deserialize, Debug, Clone)]
+pub struct CalendarProfile {
+    /// Main content of the page, holding the revised date and semester
+    pub content: CssSelector,
+    pub name: CssSelector,
+    pub table: CssSelector,
+    pub row: CssSelector,
+    pub columns: CalendarColumns,
+    /// Matches the date cell of header rows, which are skipped
+    pub header_pattern: Pattern,
+    /// Finds the revised date in the content, as its first group
+    pub revised_date_pattern: Pattern,
+    /// chrono format of the revised date
+    pub revised_date_format: String,
+    /// Finds the semester and year in the content, as its first and second groups
+    pub semester_pattern: Pattern,
+}
+
+impl CalendarProfile {
+    /// Cell of a row in a column
+    pub fn column(&self, column: NonZeroUsize) -> Selector {
+        Selector::parse(&format!("td:nth-of-type({column})")).unwrap()
+    }
+}
+
+/// Words in the header cells naming each column of an exam schedule table
+#[derive(Deserialize, Debug, Clone)]
+pub struct ExamHeaders {
+    pub date: Vec<String>,
+    pub courses: Vec<String>,
+    pub slot: Vec<String>,
+    pub room: Vec<String>,
+}
+
+/// An exam schedule page
+#[derive(Deserialize, Debug, Clone)]
+pub struct ExamProfile {
+    pub table: CssSelector,
+    pub row: CssSelector,
+    pub cell: CssSelector,
+    pub headers: ExamHeaders,
+    /// Matches a course code in a cell
+    pub course_pattern: Pattern,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ScraperProfile {
+    pub listing: ListingProfile,
+    pub calendar: CalendarProfile,
+    pub exam_schedule: ExamProfile,
+}
+
+/// Replaces the values of `base` with those of `patch`, object by object
+fn merge(base: &mut Value, patch: Value) {
+    match (base, patch) {
+        (Value::Object(base), Value::Object(patch)) => {
+            for (key, value) in patch {
+                match base.get_mut(&key) {
+                    Some(base) => merge(base, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, patch) => *base = patch,
+    }
+}
+
+impl ScraperProfile {
+    /// Loads the bundled profile with any of its parts replaced by those in `json`
+    pub fn with_overrides(json: &str) -> Result<Self, Box<dyn Error>> {
+        let mut profile: Value = serde_json::from_str(DEFAULT_PROFILE)?;
+        merge(&mut profile, serde_json::from_str(json)?);
+        let profile: ScraperProfile = serde_json::from_value(profile)?;
+
+        let calendar = &profile.calendar;
+        calendar
+            .revised_date_pattern
+            .check_groups("revised_date_pattern", 1)?;
+        calendar
+            .semester_pattern
+            .check_groups("semester_pattern", 2)?;
+        Ok(profile)
+    }
+
+    /// Bundled profile, overridden by the JSON file at `path` if there is one
+    fn load(path: Option<OsString>) -> Result<Self, String> {
+        let Some(path) = path else {
+            return Ok(
+                ScraperProfile::with_overrides("{}").expect("bundled scraper profile is valid")
+            );
+        };
+        fs::read_to_string(&path)
+            .map_err(|e| e.into())
+            .and_then(|json| ScraperProfile::with_overrides(&json))
+            .map_err(|e| {
+                format!(
+                    "scraper profile {} can't be loaded: {e}",
+                    path.to_string_lossy()
+                )
+            })
+    }
+
+    /// Bundled profile, overridden by the JSON file at `EWU_SCRAPER_PROFILE` if it is set.
+    /// An override that can't be loaded is an error on every use, rather than falling back
+    /// to the bundled profile the override was meant to fix
+    pub fn global() -> Result<&'static ScraperProfile, String> {
+        static PROFILE: OnceLock<Result<ScraperProfile, String>> = OnceLock::new();
+        PROFILE
+            .get_or_init(|| ScraperProfile::load(env::var_os("EWU_SCRAPER_PROFILE")))
+            .as_ref()
+            .map_err(String::clone)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_overrides() {
+        let profile = ScraperProfile::with_overrides("{}").unwrap();
+        assert_eq!(profile.calendar.columns.event.get(), 3);
+        assert!(profile.listing.exam_pattern.is_match("Final Exam Schedule"));
+
+        let profile = ScraperProfile::with_overrides(
+            r#"{"calendar": {"columns": {"event": 2}, "header_pattern": "^(Date|Day)"}}"#,
+        )
+        .unwrap();
+        assert_eq!(profile.calendar.columns.date.get(), 1);
+        assert_eq!(profile.calendar.columns.event.get(), 2);
+        assert!(profile.calendar.header_pattern.is_match("Day"));
+        assert_eq!(profile.exam_schedule.headers.room, vec!["room", "venue"]);
+
+        let error = ScraperProfile::with_overrides(r#"{"calendar": {"table": "table >"}}"#)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("invalid selector \"table >\""), "{error}");
+        assert!(ScraperProfile::with_overrides(r#"{"listing": {"year_tab": "[id="}}"#).is_err());
+        assert!(
+            ScraperProfile::with_overrides(r#"{"exam_schedule": {"course_pattern": "("}}"#)
+                .is_err()
+        );
+        assert!(
+            ScraperProfile::with_overrides(r#"{"calendar": {"columns": {"date": 0}}}"#).is_err()
+        );
+        let error = ScraperProfile::with_overrides(
+            r#"{"calendar": {"semester_pattern": "Spring (\\d{4})"}}"#,
+        )
+        .unwrap_err()
+        .to_string();
+        assert_eq!(error, "semester_pattern needs 2 capture group(s)");
+    }
+
+    #[test]
+    fn test_load() {
+        assert!(ScraperProfile::load(None).is_ok());
+
+        let error = ScraperProfile::load(Some("missing_profile.json".into())).unwrap_err();
+        assert!(
+            error.starts_with("scraper profile missing_profile.json"),
+            "{error}"
+        );
+    }
+}
@@ -0,0 +1,159 @@
+//! Teaching day analytics: how many class days a semester has,
+//! in total and on each day of EWU's Sunday to Thursday week
+
+use chrono::{Datelike, NaiveDate, Weekday};
+use serde::Serialize;
+use std::collections::HashSet;
+
+use crate::category::Category;
+use crate::error::ApiError;
+use crate::parser::{CalendarDetails, Entry};
+
+pub const TEACHING_WEEK: [Weekday; 5] = [
+    Weekday::Sun,
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+];
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct WeekdayCount {
+    pub weekday: String,
+    /// Days of the teaching span falling on this weekday
+    pub days: u32,
+    /// Of which are holidays
+    pub holidays: u32,
+    /// Of which have classes
+    pub classes: u32,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct SemesterStats {
+    /// First day of classes
+    pub classes_start: NaiveDate,
+    /// Last day of classes
+    pub classes_end: NaiveDate,
+    /// Days with classes, excluding weekends and holidays
+    pub teaching_days: u32,
+    /// Weekdays lost to holidays and breaks
+    pub holidays: u32,
+    pub weekdays: Vec<WeekdayCount>,
+}
+
+fn is_classes_start(event: &str) -> bool {
+    let event = event.to_lowercase();
+    event.contains("class")
+        && ["begin", "commence", "start", "first day"]
+            .iter()
+            .any(|x| event.contains(x))
+}
+
+fn is_classes_end(event: &str) -> bool {
+    let event = event.to_lowercase();
+    ["last day of class", "classes end", "end of class"]
+        .iter()
+        .any(|x| event.contains(x))
+}
+
+/// First and last day of classes, from the events announcing them
+pub fn teaching_span(calendar: &CalendarDetails) -> Result<(NaiveDate, NaiveDate), ApiError> {
+    let find = |matches: fn(&str) -> bool, what: &str| {
+        calendar
+            .entries
+            .iter()
+            .find(|entry| matches(&entry.event))
+            .ok_or_else(|| ApiError::Unprocessable(format!("Calendar has no {what} event")))
+    };
+
+    let start = find(is_classes_start, "classes begin")?.start();
+    let end = find(is_classes_end, "last day of classes")?.end();
+    if end < start {
+        return Err(ApiError::Unprocessable(format!(
+            "Classes end on {end}, before they begin on {start}"
+        )));
+    }
+
+    Ok((start, end))
+}
+
+/// Days covered by holidays and breaks
+pub fn holiday_dates(calendar: &CalendarDetails) -> HashSet<NaiveDate> {
+    calendar
+        .entries
+        .iter()
+        .filter(|entry| Category::classify(&entry.event) == Category::Holiday)
+        .flat_map(|entry: &Entry| entry.start().iter_days().take(entry.days().max(1) as usize))
+        .collect()
+}
+
+pub fn semester_stats(calendar: &CalendarDetails) -> Result<SemesterStats, ApiError> {
+    let (classes_start, classes_end) = teaching_span(calendar)?;
+    let holidays = holiday_dates(calendar);
+
+    let mut weekdays = TEACHING_WEEK
+        .iter()
+        .map(|weekday| WeekdayCount {
+            weekday: weekday.to_string(),
+            days: 0,
+            holidays: 0,
+            classes: 0,
+        })
+        .collect::<Vec<_>>();
+
+    let span = classes_start
+        .iter_days()
+        .take_while(|date| *date <= classes_end);
+    for date in span {
+        let Some(index) = TEACHING_WEEK.iter().position(|x| *x == date.weekday()) else {
+            continue;
+        };
+        let count = &mut weekdays[index];
+        count.days += 1;
+        if holidays.contains(&date) {
+            count.holidays += 1;
+        } else {
+            count.classes += 1;
+        }
+    }
+
+    Ok(SemesterStats {
+        classes_start,
+        classes_end,
+        teaching_days: weekdays.iter().map(|x| x.classes).sum(),
+        holidays: weekdays.iter().map(|x| x.holidays).sum(),
+        weekdays,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{calendar, date, entry};
+
+    #[test]
+    fn test_semester_stats() {
+        // two weeks of classes, Sunday 7 to Thursday 18 January
+        let calendar = calendar(vec![
+            entry(date(1, 4), None, "Online Advising"),
+            entry(date(1, 7), None, "Classes begin"),
+            entry(date(1, 9), Some(date(1, 10)), "Holiday"),
+            entry(date(1, 12), None, "Shab-e-Barat (Holiday)"),
+            entry(date(1, 18), None, "Last day of classes"),
+        ]);
+
+        let stats = semester_stats(&calendar).unwrap();
+
+        assert_eq!(stats.classes_start, date(1, 7));
+        assert_eq!(stats.classes_end, date(1, 18));
+        assert_eq!(stats.teaching_days, 8);
+        // the Friday holiday isn't a lost teaching day
+        assert_eq!(stats.holidays, 2);
+        let classes = stats.weekdays.iter().map(|x| x.classes).collect::<Vec<_>>();
+        assert_eq!(classes, vec![2, 2, 1, 1, 2]);
+
+        let mut no_end = calendar.clone();
+        no_end.entries.pop();
+        assert!(semester_stats(&no_end).is_err());
+    }
+}
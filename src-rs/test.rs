@@ -1,14 +1,146 @@
+use std::env;
 use std::error::Error;
+use std::fs;
 
-use ewu_ics_cal::{parser, utils};
+use ewu_ics_cal::{
+    calendar_path::CalendarPath,
+    diff::diff_calendars,
+    format::OutputFormat,
+    holidays,
+    import::parse_ics,
+    options::IcsOptions,
+    parser::CalendarDetails,
+    stats, utils,
+    webhook::{CalendarStore, WebhookDispatcher},
+};
+
+const USAGE: &str = "usage:
+  test [calendar_path] [--format FORMAT]     fetch a calendar and print it
+  test import FILE.ics [--format FORMAT]     read a saved calendar and print it
+  test diff OLD.ics [calendar_path]          compare a saved calendar with the current one
+  test holidays [calendar_path]              cross-check a calendar with public holidays
+  test stats [calendar_path]                 count the teaching days of a semester
+  test watch STATE_DIR [calendar_path...]    notify WEBHOOK_URLS of calendars changed since the
+                                             last run, every listed calendar if none are given
+
+formats: ics, jcal, xcal, csv, markdown, html, agenda";
+
+const DEFAULT_PATH: &str = "/academic-calendar-details/spring-2024-graduate";
+
+fn read_ics(file: Option<String>) -> Result<CalendarDetails, Box<dyn Error + Send + Sync>> {
+    let file = file.ok_or(USAGE)?;
+    let ics = fs::read_to_string(&file)?;
+    parse_ics(&ics).map_err(|e| format!("{file}: {e}").into())
+}
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
-    let calendar =
-        utils::fetch_calendar_details("/academic-calendar-details/spring-2024-graduate").await?;
-    let ics = parser::generate_ics(calendar);
+    let mut args = env::args().skip(1).peekable();
+
+    let command = match args.peek().map(String::as_str) {
+        Some("import") | Some("diff") | Some("holidays") | Some("stats") | Some("watch") => {
+            args.next()
+        }
+        Some("--help") | Some("-h") => {
+            println!("{USAGE}");
+            return Ok(());
+        }
+        _ => None,
+    };
+
+    let mut positional = vec![];
+    let mut format = OutputFormat::Ics;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => format = args.next().ok_or("--format needs a value")?.parse()?,
+            _ => positional.push(arg),
+        }
+    }
+    let mut positional = positional.into_iter();
+
+    let remote_path =
+        |path: Option<String>| CalendarPath::parse(path.as_deref().unwrap_or(DEFAULT_PATH));
+
+    match command.as_deref() {
+        Some("import") => {
+            let calendar = read_ics(positional.next())?;
+            println!("{}", format.render(calendar, &IcsOptions::default())?);
+        }
+        Some("diff") => {
+            let old = read_ics(positional.next())?;
+            let path = remote_path(positional.next())?;
+            let new = utils::fetch_calendar_details(&path).await?;
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&diff_calendars(&old, &new))?
+            );
+        }
+        Some("holidays") => {
+            let path = remote_path(positional.next())?;
+            let calendar = utils::fetch_calendar_details(&path).await?;
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&holidays::check(&calendar))?
+            );
+        }
+        Some("stats") => {
+            let path = remote_path(positional.next())?;
+            let calendar = utils::fetch_calendar_details(&path).await?;
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&stats::semester_stats(&calendar)?)?
+            );
+        }
+        Some("watch") => {
+            let store = CalendarStore::new(positional.next().ok_or(USAGE)?);
+            let dispatcher = WebhookDispatcher::from_env();
+            if dispatcher.urls().is_empty() {
+                return Err("WEBHOOK_URLS is not set".into());
+            }
+
+            let mut paths = positional
+                .map(|path| CalendarPath::parse(&path))
+                .collect::<Result<Vec<_>, _>>()?;
+            if paths.is_empty() {
+                paths = utils::fetch_calendar_lists()
+                    .await?
+                    .iter()
+                    .flat_map(|list| &list.programs)
+                    .flat_map(|program| &program.calendars)
+                    .filter_map(|calendar| CalendarPath::parse(&calendar.url).ok())
+                    .collect();
+            }
 
-    println!("{ics}");
+            // one calendar failing shouldn't hold back notifications of the others
+            let mut failures = 0;
+            for path in paths {
+                let result = match utils::fetch_calendar_details(&path).await {
+                    Ok(calendar) => dispatcher.watch(&store, &path, &calendar).await,
+                    Err(e) => Err(e.into()),
+                };
+                match result {
+                    Ok(true) => println!("{path}: changed, notified"),
+                    Ok(false) => println!("{path}: unchanged"),
+                    Err(e) => {
+                        eprintln!("{path}: {e}");
+                        failures += 1;
+                    }
+                }
+            }
+            if failures > 0 {
+                return Err(format!("{failures} calendars could not be watched").into());
+            }
+        }
+        _ => {
+            let path = remote_path(positional.next())?;
+            let calendar = utils::fetch_calendar_details(&path).await?;
+            let options = IcsOptions {
+                source_url: Some(path.url()),
+                ..Default::default()
+            };
+            println!("{}", format.render(calendar, &options)?);
+        }
+    }
 
     Ok(())
 }
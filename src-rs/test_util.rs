@@ -0,0 +1,92 @@
+//! Fixtures shared by the tests of several modules
+
+use chrono::NaiveDate;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::thread;
+
+use crate::parser::{CalendarDetails, Entry};
+
+/// A date in 2024
+pub(crate) fn date(month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, month, day).unwrap()
+}
+
+pub(crate) fn entry(start: NaiveDate, end: Option<NaiveDate>, event: &str) -> Entry {
+    Entry {
+        date: (start, end),
+        date_text: String::new(),
+        event: event.to_string(),
+    }
+}
+
+/// The Spring 2024 undergraduate calendar, revised on 2 January
+pub(crate) fn calendar(entries: Vec<Entry>) -> CalendarDetails {
+    CalendarDetails {
+        calendar_name: "Undergraduate".to_string(),
+        semester: "Spring".to_string(),
+        revised_date: date(1, 2),
+        year: 2024,
+        entries,
+    }
+}
+
+/// A request received by a stand-in server
+pub(crate) struct Received {
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Received {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Serves `requests` requests on a local port, answering each with what `respond` returns
+/// for it. Hands back the server's url and the requests it received
+pub(crate) fn stand_in_server(
+    requests: usize,
+    respond: impl Fn(&Received) -> &'static [u8] + Send + 'static,
+) -> (String, thread::JoinHandle<Vec<Received>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+
+    let handle = thread::spawn(move || {
+        let mut received = vec![];
+        for _ in 0..requests {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut headers = vec![];
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line == "\r\n" {
+                    break;
+                }
+                if let Some((key, value)) = line.split_once(':') {
+                    headers.push((key.to_owned(), value.trim().to_owned()));
+                }
+            }
+            let mut request = Received {
+                headers,
+                body: String::new(),
+            };
+            let content_length = request
+                .header("content-length")
+                .map_or(0, |length| length.parse().unwrap());
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+            request.body = String::from_utf8(body).unwrap();
+
+            stream.write_all(respond(&request)).unwrap();
+            received.push(request);
+        }
+        received
+    });
+
+    (url, handle)
+}
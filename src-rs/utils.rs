@@ -1,46 +1,135 @@
+use super::cache::ResponseCache;
+use super::calendar_path::CalendarPath;
+use super::error::ApiError;
+use super::exam::{self, ExamSchedule};
 use super::parser;
-use chrono::NaiveDate;
-use reqwest::header::{CACHE_CONTROL, LAST_MODIFIED};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use reqwest::header::{CACHE_CONTROL, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
 use scraper::Html;
-use std::error::Error;
+use serde::Serialize;
+use std::str::FromStr;
 use urlencoding::decode;
-use vercel_runtime::{Body, Error as VercelError, Request, Response};
+use vercel_runtime::{Body, Error as VercelError, Request, Response, StatusCode};
 
-pub async fn fetch_calendar_page() -> Result<Html, Box<dyn Error>> {
-    let resp = reqwest::get("https://www.ewubd.edu/academic-calendar")
-        .await?
-        .text()
-        .await?;
+pub async fn fetch_calendar_page() -> Result<Html, ApiError> {
+    let resp = ResponseCache::global()
+        .fetch("https://www.ewubd.edu/academic-calendar")
+        .await
+        .map_err(|e| ApiError::Upstream(e.to_string()))?;
     let doc = Html::parse_document(&resp);
     Ok(doc)
 }
 
+/// Lists every calendar on the academic calendar page
+pub async fn fetch_calendar_lists() -> Result<Vec<parser::CalendarList>, ApiError> {
+    let doc = fetch_calendar_page().await?;
+    parser::collect_all_calendars(&doc).map_err(ApiError::Unprocessable)
+}
+
 pub async fn fetch_calendar_details(
-    path: &str,
-) -> Result<parser::CalendarDetails, Box<dyn Error + Send + Sync>> {
-    let url = format!("https://www.ewubd.edu{}", path);
+    path: &CalendarPath,
+) -> Result<parser::CalendarDetails, ApiError> {
+    let url = path.url();
+
+    let raw_doc = ResponseCache::global()
+        .fetch(&url)
+        .await
+        .map_err(|e| ApiError::Upstream(e.to_string()))?;
+    let parsed_doc = Html::parse_document(&raw_doc);
+
+    parser::generate_calendar(&parsed_doc).map_err(|e| ApiError::Unprocessable(e.to_string()))
+}
+
+/// Reads a single query string parameter, if present
+pub fn get_query_param(req: &Request, name: &str) -> Result<Option<String>, ApiError> {
+    let Some(query) = req.uri().query() else {
+        return Ok(None);
+    };
+
+    let params = queryst::parse(query).map_err(|e| ApiError::BadRequest(e.message))?;
 
-    let raw_doc = reqwest::get(url).await?.text().await?;
+    match params.find(name) {
+        None => Ok(None),
+        Some(value) => {
+            let value = value
+                .as_str()
+                .ok_or_else(|| ApiError::BadRequest(format!("{name} must be a string")))?;
+            let value = decode(value).map_err(|e| ApiError::BadRequest(e.to_string()))?;
+            Ok(Some(value.into_owned()))
+        }
+    }
+}
+
+/// Reads a query string parameter and parses it into `T`
+pub fn parse_query_param<T>(req: &Request, name: &str) -> Result<Option<T>, ApiError>
+where
+    T: FromStr<Err = ApiError>,
+{
+    get_query_param(req, name)?.map(|x| x.parse()).transpose()
+}
+
+/// Reads the requested calendar from either `calendar_path`
+/// or `slug`, which the pretty `/cal/<slug>.ics` routes rewrite to
+pub fn get_calendar_path(req: &Request) -> Result<CalendarPath, ApiError> {
+    if let Some(calendar_path) = get_query_param(req, "calendar_path")? {
+        return CalendarPath::parse(&calendar_path);
+    }
+
+    match get_query_param(req, "slug")? {
+        Some(slug) => CalendarPath::from_slug(&slug),
+        None => Err(ApiError::BadRequest(
+            "calendar_path parameter is missing".to_string(),
+        )),
+    }
+}
+
+pub async fn fetch_exam_schedule(path: &CalendarPath) -> Result<ExamSchedule, ApiError> {
+    let raw_doc = ResponseCache::global()
+        .fetch(&path.url())
+        .await
+        .map_err(|e| ApiError::Upstream(e.to_string()))?;
     let parsed_doc = Html::parse_document(&raw_doc);
 
-    parser::generate_calendar(&parsed_doc).map_err(|e| VercelError::from(e.to_string()))
+    exam::parse_exam_schedule(&parsed_doc).map_err(|e| ApiError::Unprocessable(e.to_string()))
 }
 
-pub fn get_calendar_path(req: &Request) -> Result<String, Box<dyn Error + Send + Sync>> {
-    let calendar_path = req
-        .uri()
-        .query()
-        .ok_or(VercelError::from("Calendar path not found"))
-        .and_then(|x| queryst::parse(x).map_err(|e| VercelError::from(e.message)))
-        .and_then(|v| {
-            v.find("calendar_path")
-                .ok_or(VercelError::from("Calendar path not found"))
-                .map(|v| v.to_owned())
-        })?;
+/// Rejects a path missing from the listing.
+/// A listing that can't be fetched or has no calendars, as after a redesign the scraper
+/// doesn't follow yet, can't tell which calendars exist, so both let any path through.
+/// Paths are checked by `CalendarPath` either way, so only ewubd.edu calendar pages are fetched
+fn check_listed(
+    path: CalendarPath,
+    lists: Result<Vec<parser::CalendarList>, ApiError>,
+) -> Result<CalendarPath, ApiError> {
+    let has_calendars = |lists: &[parser::CalendarList]| {
+        lists
+            .iter()
+            .flat_map(|list| &list.programs)
+            .any(|program| !program.calendars.is_empty() || !program.exam_schedules.is_empty())
+    };
 
-    let calendar_path = decode(calendar_path.as_str().unwrap())?.into_owned();
+    match lists {
+        Ok(lists) if has_calendars(&lists) && !path.is_listed(&lists) => Err(ApiError::BadRequest(
+            "Not a listed calendar, see /api/calendars for those available".to_string(),
+        )),
+        _ => Ok(path),
+    }
+}
 
-    Ok(calendar_path)
+/// Reads the requested calendar path, rejecting pages missing from the listing
+async fn listed_calendar_path(req: &Request) -> Result<CalendarPath, ApiError> {
+    let calendar_remote_path = get_calendar_path(req)?;
+    check_listed(calendar_remote_path, fetch_calendar_lists().await)
+}
+
+/// Resolves the calendar requested by `calendar_path`
+pub async fn calendar_from_request(req: &Request) -> Result<parser::CalendarDetails, ApiError> {
+    fetch_calendar_details(&listed_calendar_path(req).await?).await
+}
+
+/// Resolves the exam schedule requested by `calendar_path`
+pub async fn exam_schedule_from_request(req: &Request) -> Result<ExamSchedule, ApiError> {
+    fetch_exam_schedule(&listed_calendar_path(req).await?).await
 }
 
 pub fn cache_headers(res: &mut Response<Body>) -> &mut Response<Body> {
@@ -54,7 +143,281 @@ pub fn last_modified_header(res: &mut Response<Body>, last_modified: NaiveDate)
     let headers = res.headers_mut();
     headers.insert(
         LAST_MODIFIED,
-        last_modified.format("%a, %d %b %Y 00:00:00 GMT").to_string().parse().unwrap(),
+        format_http_date(last_modified.and_time(NaiveTime::MIN)).parse().unwrap(),
     );
     res
 }
+
+/// Formats a UTC timestamp as an IMF-fixdate, eg: Sun, 06 Nov 1994 08:49:37 GMT
+pub fn format_http_date(date: NaiveDateTime) -> String {
+    date.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+/// Parses any of the three HTTP date formats allowed by RFC 7231:
+/// IMF-fixdate, the obsolete RFC 850 format and ANSI C's asctime() format.
+/// Invalid dates yield `None` so callers can ignore the header
+pub fn parse_http_date(date: &str) -> Option<NaiveDateTime> {
+    const FORMATS: [&str; 3] = [
+        "%a, %d %b %Y %H:%M:%S GMT",
+        "%A, %d-%b-%y %H:%M:%S GMT",
+        "%a %b %e %H:%M:%S %Y",
+    ];
+
+    FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(date.trim(), format).ok())
+}
+
+/// A calendar or exam schedule, whose responses carry validators
+pub trait Revisable: Serialize {
+    /// Date the university last revised it, if its page says
+    fn revised_date(&self) -> Option<NaiveDate>;
+}
+
+impl Revisable for parser::CalendarDetails {
+    fn revised_date(&self) -> Option<NaiveDate> {
+        Some(self.revised_date)
+    }
+}
+
+impl Revisable for ExamSchedule {
+    fn revised_date(&self) -> Option<NaiveDate> {
+        None
+    }
+}
+
+/// Strong validator derived from the calendar contents.
+/// The query string is included since it selects the representation, eg: `format`
+pub fn calendar_etag(calendar: &impl Serialize, req: &Request) -> String {
+    let content = serde_json::to_string(calendar).unwrap_or_default();
+    let query = req.uri().query().unwrap_or_default();
+    let hash = xxhash_rust::xxh3::xxh3_64(format!("{content}?{query}").as_bytes());
+    format!("\"{:x}\"", hash)
+}
+
+pub fn etag_header<'a>(res: &'a mut Response<Body>, etag: &str) -> &'a mut Response<Body> {
+    let headers = res.headers_mut();
+    headers.insert(ETAG, etag.parse().unwrap());
+    res
+}
+
+/// Evaluates conditional request headers.
+/// `If-None-Match` takes precedence over `If-Modified-Since` when both are sent,
+/// and the latter is ignored without a `last_modified` date
+pub fn is_not_modified(req: &Request, etag: &str, last_modified: Option<NaiveDate>) -> bool {
+    let headers = req.headers();
+
+    if let Some(if_none_match) = headers.get(IF_NONE_MATCH) {
+        return if_none_match.to_str().is_ok_and(|x| {
+            x.split(',')
+                .map(|tag| tag.trim().trim_start_matches("W/"))
+                .any(|tag| tag == "*" || tag == etag)
+        });
+    }
+
+    headers
+        .get(IF_MODIFIED_SINCE)
+        .and_then(|x| x.to_str().ok())
+        .and_then(parse_http_date)
+        .zip(last_modified)
+        .is_some_and(|(since, last_modified)| since.date() >= last_modified)
+}
+
+/// Responds to a request for a representation of `calendar`, answering a conditional
+/// request with 304 if the calendar is unchanged and otherwise building the response with
+/// `respond`. Successful responses carry the calendar's validators and cache headers
+pub fn calendar_response(
+    req: &Request,
+    calendar: &impl Revisable,
+    respond: impl FnOnce() -> Result<Response<Body>, VercelError>,
+) -> Result<Response<Body>, VercelError> {
+    let etag = calendar_etag(calendar, req);
+    let revised_date = calendar.revised_date();
+
+    let mut resp = if is_not_modified(req, &etag, revised_date) {
+        Response::builder()
+            .status(StatusCode::NOT_MODIFIED)
+            .body(Body::Empty)?
+    } else {
+        respond()?
+    };
+
+    // errors aren't cached, so a fixed upstream shows up right away
+    if resp.status().is_success() || resp.status() == StatusCode::NOT_MODIFIED {
+        etag_header(&mut resp, &etag);
+        if let Some(revised_date) = revised_date {
+            last_modified_header(&mut resp, revised_date);
+        }
+        cache_headers(&mut resp);
+    }
+    Ok(resp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(headers: &[(&'static str, &str)]) -> Request {
+        let mut req = Request::new(Body::Empty);
+        for (key, value) in headers {
+            req.headers_mut().insert(*key, value.parse().unwrap());
+        }
+        req
+    }
+
+    fn request_with_query(query: &str) -> Request {
+        let mut req = Request::new(Body::Empty);
+        *req.uri_mut() = format!("/api/generate?{query}").parse().unwrap();
+        req
+    }
+
+    #[test]
+    fn test_get_calendar_path() {
+        let expected = CalendarPath::from_slug("spring-2024-graduate").unwrap();
+
+        assert_eq!(
+            get_calendar_path(&request_with_query(
+                "calendar_path=%2Facademic-calendar-details%2Fspring-2024-graduate"
+            )),
+            Ok(expected.clone())
+        );
+        assert_eq!(
+            get_calendar_path(&request_with_query("slug=spring-2024-graduate")),
+            Ok(expected)
+        );
+        assert!(get_calendar_path(&request_with_query("slug=..%2Fadmin")).is_err());
+        assert!(get_calendar_path(&request_with_query("format=ics")).is_err());
+    }
+
+    #[test]
+    fn test_parse_http_date() {
+        let expected = NaiveDate::from_ymd_opt(1994, 11, 6)
+            .unwrap()
+            .and_hms_opt(8, 49, 37);
+
+        assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"), expected);
+        assert_eq!(parse_http_date("Sunday, 06-Nov-94 08:49:37 GMT"), expected);
+        assert_eq!(parse_http_date("Sun Nov  6 08:49:37 1994"), expected);
+        assert_eq!(parse_http_date("yesterday"), None);
+        assert_eq!(parse_http_date("Sun, 32 Nov 1994 08:49:37 GMT"), None);
+        assert_eq!(
+            format_http_date(expected.unwrap()),
+            "Sun, 06 Nov 1994 08:49:37 GMT"
+        );
+    }
+
+    #[test]
+    fn test_is_not_modified() {
+        let revised = NaiveDate::from_ymd_opt(2024, 1, 10);
+        let etag = "\"abc\"";
+
+        assert!(!is_not_modified(&request(&[]), etag, revised));
+        assert!(is_not_modified(
+            &request(&[("If-None-Match", "\"xyz\", W/\"abc\"")]),
+            etag,
+            revised
+        ));
+        assert!(!is_not_modified(&request(&[("If-None-Match", "\"xyz\"")]), etag, revised));
+        assert!(is_not_modified(
+            &request(&[("If-Modified-Since", "Wed, 10 Jan 2024 00:00:00 GMT")]),
+            etag,
+            revised
+        ));
+        assert!(!is_not_modified(
+            &request(&[("If-Modified-Since", "Tue, 09 Jan 2024 00:00:00 GMT")]),
+            etag,
+            revised
+        ));
+        assert!(!is_not_modified(
+            &request(&[("If-Modified-Since", "not a date")]),
+            etag,
+            revised
+        ));
+        // a matching date doesn't override a mismatching etag
+        assert!(!is_not_modified(
+            &request(&[
+                ("If-None-Match", "\"xyz\""),
+                ("If-Modified-Since", "Wed, 10 Jan 2024 00:00:00 GMT")
+            ]),
+            etag,
+            revised
+        ));
+    }
+
+    #[test]
+    fn test_calendar_response() {
+        let calendar = crate::test_util::calendar(vec![]);
+        let ok = || Ok(Response::new(Body::from("calendar")));
+
+        let resp = calendar_response(&request(&[]), &calendar, ok).unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let etag = resp.headers()[ETAG].to_str().unwrap().to_owned();
+        assert!(resp.headers().contains_key(CACHE_CONTROL));
+
+        let resp = calendar_response(&request(&[("If-None-Match", &etag)]), &calendar, || {
+            unreachable!("an unchanged calendar isn't rendered")
+        })
+        .unwrap();
+        assert_eq!(resp.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(resp.headers()[LAST_MODIFIED], "Tue, 02 Jan 2024 00:00:00 GMT");
+
+        let resp = calendar_response(&request(&[]), &calendar, || {
+            ApiError::Unprocessable("no classes".to_string()).to_response()
+        })
+        .unwrap();
+        assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(!resp.headers().contains_key(CACHE_CONTROL));
+
+        // exam schedules have no revised date, so only their etag is checked
+        let schedule = ExamSchedule {
+            name: "Final Exam Schedule Spring 2024".to_string(),
+            semester: "Spring".to_string(),
+            year: 2024,
+            slots: vec![],
+            skipped: vec![],
+        };
+        let resp = calendar_response(&request(&[]), &schedule, ok).unwrap();
+        let etag = resp.headers()[ETAG].to_str().unwrap().to_owned();
+        assert!(!resp.headers().contains_key(LAST_MODIFIED));
+        let resp = calendar_response(&request(&[("If-None-Match", &etag)]), &schedule, || {
+            unreachable!("an unchanged schedule isn't rendered")
+        })
+        .unwrap();
+        assert_eq!(resp.status(), StatusCode::NOT_MODIFIED);
+        let since = [("If-Modified-Since", "Tue, 02 Jan 2024 00:00:00 GMT")];
+        let resp = calendar_response(&request(&since), &schedule, ok).unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn test_check_listed() {
+        let path = || CalendarPath::from_slug("spring-2024-graduate").unwrap();
+        let lists = |url: &str| {
+            vec![parser::CalendarList {
+                year: "2024".to_string(),
+                programs: vec![parser::Program {
+                    program_type: "Graduate".to_string(),
+                    calendars: vec![parser::Calendar {
+                        name: "Spring 2024".to_string(),
+                        url: url.to_string(),
+                    }],
+                    exam_schedules: vec![],
+                }],
+            }]
+        };
+
+        assert_eq!(check_listed(path(), Ok(lists(path().as_str()))), Ok(path()));
+        assert!(check_listed(path(), Ok(lists("/academic-calendar-details/fall-2024"))).is_err());
+        // nothing to check against either way
+        assert_eq!(
+            check_listed(path(), Err(ApiError::Upstream("timed out".to_string()))),
+            Ok(path())
+        );
+        assert_eq!(check_listed(path(), Ok(vec![])), Ok(path()));
+        let no_programs = parser::CalendarList {
+            year: "2024".to_string(),
+            programs: vec![],
+        };
+        assert_eq!(check_listed(path(), Ok(vec![no_programs])), Ok(path()));
+    }
+}
@@ -0,0 +1,329 @@
+//! Stable response model for the `/api/v1` endpoints.
+//! These structs are decoupled from the parser's internal ones,
+//! so the parser can change without breaking clients of v1
+
+use chrono::NaiveDate;
+use schemars::{gen::SchemaSettings, schema_for, JsonSchema};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::calendar_path::CalendarPath;
+use crate::date_conversion::{to_bangabda, to_hijri, ConvertedDate};
+use crate::holidays::official_holidays;
+use crate::locale::{localize, Lang};
+use crate::parser;
+
+/// A single event on the academic calendar
+#[derive(Serialize, JsonSchema, Debug, PartialEq)]
+pub struct Entry {
+    /// First day of the event
+    pub start: NaiveDate,
+    /// Last day of the event, inclusive. Same as `start` for single day events
+    pub end: NaiveDate,
+    pub title: String,
+    /// `start` and `end` in the revised Bangla calendar
+    pub bangabda: DateSpan,
+    /// `start` and `end` in the tabular Hijri calendar, which may be a day off the observed one
+    pub hijri: DateSpan,
+    /// Names of the Bangladesh public holidays a holiday falls on
+    pub official_holidays: Vec<String>,
+}
+
+#[derive(Serialize, JsonSchema, Debug, PartialEq)]
+pub struct DateSpan {
+    pub start: ConvertedDate,
+    pub end: ConvertedDate,
+}
+
+/// An academic calendar of a semester
+#[derive(Serialize, JsonSchema, Debug, PartialEq)]
+pub struct CalendarDetails {
+    pub name: String,
+    pub semester: String,
+    pub year: i32,
+    /// Date the university last revised the calendar
+    pub revised_date: NaiveDate,
+    pub entries: Vec<Entry>,
+}
+
+#[derive(Serialize, JsonSchema, Debug, PartialEq)]
+pub struct Calendar {
+    pub name: String,
+    /// Value to pass as `calendar_path` to other endpoints
+    pub path: String,
+    /// Value to use in `/cal/<slug>.ics` subscription urls, if the path has one
+    pub slug: Option<String>,
+}
+
+#[derive(Serialize, JsonSchema, Debug, PartialEq)]
+pub struct Program {
+    pub name: String,
+    pub calendars: Vec<Calendar>,
+    /// Exam schedules, whose path can be passed to `/api/exams`
+    pub exam_schedules: Vec<Calendar>,
+}
+
+#[derive(Serialize, JsonSchema, Debug, PartialEq)]
+pub struct CalendarList {
+    pub year: String,
+    pub programs: Vec<Program>,
+}
+
+impl From<&parser::Entry> for Entry {
+    fn from(entry: &parser::Entry) -> Self {
+        Entry {
+            start: entry.start(),
+            end: entry.end(),
+            title: entry.event.clone(),
+            bangabda: DateSpan {
+                start: to_bangabda(entry.start()),
+                end: to_bangabda(entry.end()),
+            },
+            hijri: DateSpan {
+                start: to_hijri(entry.start()),
+                end: to_hijri(entry.end()),
+            },
+            official_holidays: official_holidays(entry)
+                .into_iter()
+                .map(|x| x.name.clone())
+                .collect(),
+        }
+    }
+}
+
+impl From<&parser::CalendarDetails> for CalendarDetails {
+    fn from(calendar: &parser::CalendarDetails) -> Self {
+        CalendarDetails {
+            name: calendar.calendar_name.clone(),
+            semester: calendar.semester.clone(),
+            year: calendar.year,
+            revised_date: calendar.revised_date,
+            entries: calendar.entries.iter().map(Entry::from).collect(),
+        }
+    }
+}
+
+impl CalendarDetails {
+    /// The calendar in `lang`. Public holidays are found from the English text,
+    /// so translation comes last
+    pub fn new(calendar: &parser::CalendarDetails, lang: Lang) -> Self {
+        let localized = localize(calendar, lang);
+        let mut details = CalendarDetails::from(calendar);
+        details.name = localized.calendar_name;
+        details.semester = localized.semester;
+        for (entry, localized) in details.entries.iter_mut().zip(localized.entries) {
+            entry.title = localized.event;
+        }
+        details
+    }
+}
+
+impl From<&parser::Calendar> for Calendar {
+    fn from(calendar: &parser::Calendar) -> Self {
+        Calendar {
+            name: calendar.name.clone(),
+            path: calendar.url.clone(),
+            slug: CalendarPath::parse(&calendar.url)
+                .ok()
+                .map(|path| path.slug().to_owned()),
+        }
+    }
+}
+
+impl From<&parser::CalendarList> for CalendarList {
+    fn from(list: &parser::CalendarList) -> Self {
+        CalendarList {
+            year: list.year.clone(),
+            programs: list
+                .programs
+                .iter()
+                .map(|program| Program {
+                    name: program.program_type.clone(),
+                    calendars: program.calendars.iter().map(Calendar::from).collect(),
+                    exam_schedules: program.exam_schedules.iter().map(Calendar::from).collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// JSON Schema of the entries response
+pub fn calendar_details_schema() -> Value {
+    json!(schema_for!(CalendarDetails))
+}
+
+/// JSON Schema of the calendars response
+pub fn calendar_lists_schema() -> Value {
+    json!(schema_for!(Vec<CalendarList>))
+}
+
+/// OpenAPI document describing the v1 endpoints
+pub fn openapi() -> Value {
+    let problem = json!({
+        "description": "RFC 7807 problem details",
+        "content": { "application/problem+json": {} }
+    });
+    let calendar_path_params = json!([
+        {
+            "name": "calendar_path",
+            "in": "query",
+            "description": "Calendar path, eg: /academic-calendar-details/spring-2024-graduate",
+            "schema": { "type": "string" }
+        },
+        {
+            "name": "slug",
+            "in": "query",
+            "description": "Calendar slug, eg: spring-2024-graduate. Used if calendar_path is absent",
+            "schema": { "type": "string" }
+        }
+    ]);
+    // the response schemas share their definitions through components, where their refs point
+    let mut settings = SchemaSettings::draft07();
+    settings.definitions_path = "#/components/schemas/".to_owned();
+    let mut generator = settings.into_generator();
+    let calendar_lists = generator.subschema_for::<Vec<CalendarList>>();
+    let calendar_details = generator.subschema_for::<CalendarDetails>();
+    let schemas = generator.take_definitions();
+
+    let lang_param = json!({
+        "name": "lang",
+        "in": "query",
+        "description": "Language of the text: en (default), bn or bn-en for Bengali with English",
+        "schema": { "type": "string", "enum": ["en", "bn", "bn-en"] }
+    });
+
+    json!({
+        "openapi": "3.1.0",
+        "info": {
+            "title": "EWU Calendar API",
+            "version": "1"
+        },
+        "paths": {
+            "/api/v1/calendars": {
+                "get": {
+                    "summary": "List academic calendars",
+                    "responses": {
+                        "200": {
+                            "description": "Calendars grouped by year and program",
+                            "content": {
+                                "application/json": { "schema": calendar_lists }
+                            }
+                        },
+                        "502": problem
+                    }
+                }
+            },
+            "/api/v1/entries": {
+                "get": {
+                    "summary": "Get the events of an academic calendar",
+                    "parameters": [calendar_path_params[0], calendar_path_params[1], lang_param],
+                    "responses": {
+                        "200": {
+                            "description": "The calendar and its events",
+                            "content": {
+                                "application/json": { "schema": calendar_details }
+                            }
+                        },
+                        "304": { "description": "Calendar hasn't changed" },
+                        "400": problem,
+                        "422": problem,
+                        "502": problem
+                    }
+                }
+            }
+        },
+        "components": {
+            "schemas": schemas
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{calendar, date, entry};
+
+    #[test]
+    fn test_entries_shape() {
+        let calendar = calendar(vec![entry(date(1, 5), None, "Classes begin")]);
+
+        // pins the public shape. Fields may be added, but changing or removing one
+        // requires a new api version
+        assert_eq!(
+            json!(CalendarDetails::from(&calendar)),
+            json!({
+                "name": "Undergraduate",
+                "semester": "Spring",
+                "year": 2024,
+                "revised_date": "2024-01-02",
+                "entries": [
+                    {
+                        "start": "2024-01-05",
+                        "end": "2024-01-05",
+                        "title": "Classes begin",
+                        "bangabda": {
+                            "start": { "year": 1430, "month": 9, "day": 21, "month_name": "Poush" },
+                            "end": { "year": 1430, "month": 9, "day": 21, "month_name": "Poush" }
+                        },
+                        "hijri": {
+                            "start": { "year": 1445, "month": 6, "day": 23, "month_name": "Jumada al-Akhirah" },
+                            "end": { "year": 1445, "month": 6, "day": 23, "month_name": "Jumada al-Akhirah" }
+                        },
+                        "official_holidays": []
+                    }
+                ]
+            })
+        );
+
+        let schema = calendar_details_schema();
+        assert_eq!(
+            schema["definitions"]["Entry"]["required"],
+            json!([
+                "bangabda",
+                "end",
+                "hijri",
+                "official_holidays",
+                "start",
+                "title"
+            ])
+        );
+    }
+
+    #[test]
+    fn test_translated_entries() {
+        let calendar = calendar(vec![entry(date(3, 26), None, "Independence Day (Holiday)")]);
+
+        let details = CalendarDetails::new(&calendar, Lang::Bn);
+        assert_ne!(details.entries[0].title, "Independence Day (Holiday)");
+        assert_eq!(
+            details.entries[0].official_holidays,
+            vec!["Independence Day"]
+        );
+    }
+
+    #[test]
+    fn test_openapi_refs_resolve() {
+        fn refs<'a>(value: &'a Value, found: &mut Vec<&'a str>) {
+            match value {
+                Value::Object(object) => {
+                    if let Some(Value::String(reference)) = object.get("$ref") {
+                        found.push(reference);
+                    }
+                    object.values().for_each(|value| refs(value, found));
+                }
+                Value::Array(array) => array.iter().for_each(|value| refs(value, found)),
+                _ => {}
+            }
+        }
+
+        let openapi = openapi();
+        let mut found = vec![];
+        refs(&openapi, &mut found);
+
+        assert!(found.contains(&"#/components/schemas/CalendarDetails"));
+        for reference in found {
+            let pointer = reference.strip_prefix('#').unwrap();
+            assert!(openapi.pointer(pointer).is_some(), "{reference} dangles");
+        }
+    }
+}
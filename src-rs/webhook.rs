@@ -0,0 +1,260 @@
+use chrono::NaiveDate;
+use reqwest::header::CONTENT_TYPE;
+use serde::Serialize;
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use crate::calendar_path::CalendarPath;
+use crate::diff::{diff_calendars, DiffSummary};
+use crate::parser::CalendarDetails;
+
+#[derive(Serialize, Debug)]
+pub struct WebhookPayload {
+    pub calendar_path: String,
+    pub old_revised_date: NaiveDate,
+    pub new_revised_date: NaiveDate,
+    pub diff: DiffSummary,
+}
+
+impl WebhookPayload {
+    /// Builds a payload if anything changed between two revisions of a calendar
+    pub fn from_change(
+        calendar_path: &str,
+        old: &CalendarDetails,
+        new: &CalendarDetails,
+    ) -> Option<Self> {
+        let diff = diff_calendars(old, new);
+
+        if diff.is_empty() && old.revised_date == new.revised_date {
+            return None;
+        }
+
+        Some(WebhookPayload {
+            calendar_path: calendar_path.to_owned(),
+            old_revised_date: old.revised_date,
+            new_revised_date: new.revised_date,
+            diff,
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct WebhookDispatcher {
+    urls: Vec<String>,
+    client: reqwest::Client,
+}
+
+impl WebhookDispatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads a comma separated list of webhook urls from `WEBHOOK_URLS`
+    pub fn from_env() -> Self {
+        let mut dispatcher = Self::new();
+        if let Ok(urls) = env::var("WEBHOOK_URLS") {
+            urls.split(',')
+                .map(str::trim)
+                .filter(|url| !url.is_empty())
+                .for_each(|url| dispatcher.register(url));
+        }
+        dispatcher
+    }
+
+    pub fn register(&mut self, url: &str) {
+        self.urls.push(url.to_owned());
+    }
+
+    pub fn urls(&self) -> &[String] {
+        &self.urls
+    }
+
+    /// Posts the payload to every registered url.
+    /// Delivery continues past failing urls, which are reported together afterwards
+    pub async fn dispatch(
+        &self,
+        payload: &WebhookPayload,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let body = serde_json::to_string(payload)?;
+        let mut failures = vec![];
+
+        for url in &self.urls {
+            let result = self
+                .client
+                .post(url)
+                .header(CONTENT_TYPE, "application/json")
+                .body(body.clone())
+                .send()
+                .await
+                .and_then(|resp| resp.error_for_status());
+
+            if let Err(e) = result {
+                failures.push(format!("{url}: {e}"));
+            }
+        }
+
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("Webhook delivery failed for {}", failures.join(", ")).into())
+        }
+    }
+
+    /// Dispatches a notification only if the calendar changed
+    pub async fn notify_if_changed(
+        &self,
+        calendar_path: &str,
+        old: &CalendarDetails,
+        new: &CalendarDetails,
+    ) -> Result<bool, Box<dyn Error + Send + Sync>> {
+        match WebhookPayload::from_change(calendar_path, old, new) {
+            Some(payload) => self.dispatch(&payload).await.map(|_| true),
+            None => Ok(false),
+        }
+    }
+
+    /// Compares a calendar with the revision last seen in `store` and notifies of any change.
+    /// The calendar is stored only once delivered, so a failed delivery is retried on the
+    /// next run. A calendar seen for the first time is stored without a notification
+    pub async fn watch(
+        &self,
+        store: &CalendarStore,
+        calendar_path: &CalendarPath,
+        calendar: &CalendarDetails,
+    ) -> Result<bool, Box<dyn Error + Send + Sync>> {
+        let sent = match store.load(calendar_path)? {
+            Some(old) => {
+                self.notify_if_changed(calendar_path.as_str(), &old, calendar)
+                    .await?
+            }
+            None => false,
+        };
+        store.save(calendar_path, calendar)?;
+        Ok(sent)
+    }
+}
+
+/// The last seen revision of each watched calendar, as a JSON file per calendar in a directory
+#[derive(Debug, Clone)]
+pub struct CalendarStore {
+    dir: PathBuf,
+}
+
+impl CalendarStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        CalendarStore { dir: dir.into() }
+    }
+
+    fn file(&self, calendar_path: &CalendarPath) -> PathBuf {
+        self.dir.join(format!("{}.json", calendar_path.slug()))
+    }
+
+    pub fn load(
+        &self,
+        calendar_path: &CalendarPath,
+    ) -> Result<Option<CalendarDetails>, Box<dyn Error + Send + Sync>> {
+        let file = self.file(calendar_path);
+        match fs::read_to_string(&file) {
+            Ok(json) => serde_json::from_str(&json)
+                .map(Some)
+                .map_err(|e| format!("{}: {e}", file.display()).into()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(format!("{}: {e}", file.display()).into()),
+        }
+    }
+
+    pub fn save(
+        &self,
+        calendar_path: &CalendarPath,
+        calendar: &CalendarDetails,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        fs::create_dir_all(&self.dir)?;
+        fs::write(self.file(calendar_path), serde_json::to_string(calendar)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{date, entry, stand_in_server};
+
+    const NO_CONTENT: &[u8] = b"HTTP/1.1 204 No Content\r\nContent-Length: 0\r\n\r\n";
+
+    fn calendar(revised_day: u32, event: &str) -> CalendarDetails {
+        CalendarDetails {
+            revised_date: date(1, revised_day),
+            ..crate::test_util::calendar(vec![entry(date(2, 1), None, event)])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_to_stand_in() {
+        let (url, handle) = stand_in_server(1, |_| NO_CONTENT);
+        let mut dispatcher = WebhookDispatcher::new();
+        dispatcher.register(&format!("{url}/hook"));
+
+        let old = calendar(1, "Classes begin");
+        let new = calendar(3, "Orientation");
+        let path = "/academic-calendar-details/spring-2024-undergraduate";
+
+        assert!(dispatcher
+            .notify_if_changed(path, &old, &new)
+            .await
+            .unwrap());
+
+        let body: serde_json::Value =
+            serde_json::from_str(&handle.join().unwrap()[0].body).unwrap();
+        assert_eq!(body["calendar_path"], path);
+        assert_eq!(body["old_revised_date"], "2024-01-01");
+        assert_eq!(body["new_revised_date"], "2024-01-03");
+        assert_eq!(body["diff"]["added"][0]["event"], "Orientation");
+        assert_eq!(body["diff"]["removed"][0]["event"], "Classes begin");
+    }
+
+    #[tokio::test]
+    async fn test_unchanged_calendar_is_not_dispatched() {
+        let mut dispatcher = WebhookDispatcher::new();
+        dispatcher.register("http://127.0.0.1:9/unreachable");
+
+        let cal = calendar(1, "Classes begin");
+        let sent = dispatcher
+            .notify_if_changed("/x", &cal, &cal)
+            .await
+            .unwrap();
+
+        assert!(!sent);
+    }
+
+    #[tokio::test]
+    async fn test_watch() {
+        let dir = env::temp_dir().join(format!("ewu-webhook-watch-{}", std::process::id()));
+        let store = CalendarStore::new(&dir);
+        let path =
+            CalendarPath::parse("/academic-calendar-details/spring-2024-undergraduate").unwrap();
+        let old = calendar(1, "Classes begin");
+        let new = calendar(3, "Orientation");
+
+        let mut unreachable = WebhookDispatcher::new();
+        unreachable.register("http://127.0.0.1:9/unreachable");
+        // the first sight of a calendar is only stored
+        assert!(!unreachable.watch(&store, &path, &old).await.unwrap());
+        assert_eq!(store.load(&path).unwrap(), Some(old.clone()));
+        // a failed delivery keeps the old revision, to be retried
+        assert!(unreachable.watch(&store, &path, &new).await.is_err());
+        assert_eq!(store.load(&path).unwrap(), Some(old));
+
+        let (url, handle) = stand_in_server(1, |_| NO_CONTENT);
+        let mut dispatcher = WebhookDispatcher::new();
+        dispatcher.register(&url);
+        assert!(dispatcher.watch(&store, &path, &new).await.unwrap());
+        assert_eq!(handle.join().unwrap().len(), 1);
+        assert_eq!(store.load(&path).unwrap(), Some(new.clone()));
+        assert!(!dispatcher.watch(&store, &path, &new).await.unwrap());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
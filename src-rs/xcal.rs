@@ -0,0 +1,136 @@
+//! xCal (RFC 6321), the XML format for iCalendar
+
+use std::error::Error;
+use std::fmt::Write;
+
+use crate::ical::{self, Component, Property, Value};
+
+pub fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn write_value(out: &mut String, value: &Value) {
+    let tag = value.type_name();
+    let content = match value {
+        Value::Recur(parts) => parts
+            .iter()
+            .flat_map(|(key, values)| {
+                values
+                    .iter()
+                    .map(move |x| format!("<{key}>{}</{key}>", escape_xml(x)))
+            })
+            .collect(),
+        Value::Integer(x) => x.to_string(),
+        Value::Float(x) => x.to_string(),
+        Value::Text(x)
+        | Value::Date(x)
+        | Value::DateTime(x)
+        | Value::UtcOffset(x)
+        | Value::Duration(x)
+        | Value::Uri(x)
+        | Value::CalAddress(x)
+        | Value::Unknown(x) => escape_xml(x),
+    };
+    let _ = write!(out, "<{tag}>{content}</{tag}>");
+}
+
+fn write_property(out: &mut String, property: &Property) {
+    let name = property.name.to_lowercase();
+    let _ = write!(out, "<{name}>");
+
+    let params = property
+        .params
+        .iter()
+        .filter(|(key, _)| key != "VALUE")
+        .collect::<Vec<_>>();
+    if !params.is_empty() {
+        out.push_str("<parameters>");
+        for (key, value) in params {
+            let key = key.to_lowercase();
+            let _ = write!(out, "<{key}><text>{}</text></{key}>", escape_xml(value));
+        }
+        out.push_str("</parameters>");
+    }
+
+    let values = property.values();
+    match values.as_slice() {
+        [Value::Float(latitude), Value::Float(longitude)] if name == "geo" => {
+            let _ = write!(
+                out,
+                "<latitude>{latitude}</latitude><longitude>{longitude}</longitude>"
+            );
+        }
+        _ => values.iter().for_each(|value| write_value(out, value)),
+    }
+
+    let _ = write!(out, "</{name}>");
+}
+
+fn write_component(out: &mut String, component: &Component) {
+    let name = component.name.to_lowercase();
+    let _ = write!(out, "<{name}>");
+
+    if !component.properties.is_empty() {
+        out.push_str("<properties>");
+        component
+            .properties
+            .iter()
+            .for_each(|property| write_property(out, property));
+        out.push_str("</properties>");
+    }
+
+    if !component.components.is_empty() {
+        out.push_str("<components>");
+        component
+            .components
+            .iter()
+            .for_each(|child| write_component(out, child));
+        out.push_str("</components>");
+    }
+
+    let _ = write!(out, "</{name}>");
+}
+
+/// Converts iCalendar text into xCal
+pub fn from_ics(ics: &str) -> Result<String, Box<dyn Error>> {
+    let calendar = ical::parse(ics)?;
+
+    let mut out = String::from(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<icalendar xmlns=\"urn:ietf:params:xml:ns:icalendar-2.0\">",
+    );
+    write_component(&mut out, &calendar);
+    out.push_str("</icalendar>\n");
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_ics() {
+        let ics = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nBEGIN:VEVENT\r\nDTSTART;VALUE=DATE:20240105\r\nSUMMARY;LANGUAGE=en:Q&A <session>\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+
+        let xcal = from_ics(ics).unwrap();
+
+        assert!(xcal.contains(
+            "<vcalendar><properties><version><text>2.0</text></version></properties><components><vevent>"
+        ));
+        assert!(xcal.contains("<dtstart><date>2024-01-05</date></dtstart>"));
+        assert!(xcal.contains(
+            "<summary><parameters><language><text>en</text></language></parameters><text>Q&amp;A &lt;session&gt;</text></summary>"
+        ));
+    }
+}